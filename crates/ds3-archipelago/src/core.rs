@@ -12,7 +12,10 @@ use regex_macro::regex;
 use crate::item::{EquipParamExt, ItemIdExt};
 use crate::save_data::*;
 use crate::slot_data::{DeathLinkOption, I64Key, SlotData};
-use shared::{Core as SharedCore, CoreBase};
+use shared::{
+    Core as SharedCore, CoreBase, DeathLinkDirection, DeathLinkEvent, DeathLinkStatus,
+    DynSaveStore, GameEvent, GameType, GoalRequirement, tr,
+};
 
 /// The grace period after either sending or receiving a death link during which
 /// no further death links will be sent or received.
@@ -42,6 +45,10 @@ pub struct Core {
     /// started a session).
     last_death_link: Instant,
 
+    /// The most recent death link the player sent or received this session,
+    /// for display in the overlay.
+    last_death_link_event: Option<DeathLinkEvent>,
+
     /// Whether the player has achieved their goal and sent that information to
     /// the Archipelago server. This is stored here rather than in the save data
     /// so that it's resent every time the player starts the game, just in case
@@ -61,6 +68,7 @@ impl shared::Core for Core {
             locations_sent: 0,
             shop_items_hinted: Default::default(),
             last_death_link: Instant::now(),
+            last_death_link_event: None,
             sent_goal: false,
         })
     }
@@ -78,7 +86,7 @@ impl shared::Core for Core {
     /// has encountered a fatal error.
     fn update_live(&mut self) -> Result<()> {
         self.check_seed_conflict()?;
-        if let Some(save_data) = SaveData::instance_mut().as_mut()
+        if let Some(save_data) = SAVE_DATA.instance_mut().as_mut()
             && save_data.seed.is_none()
         {
             save_data.seed = Some(self.seed().to_string());
@@ -90,8 +98,14 @@ impl shared::Core for Core {
         // loaded and is actively playing.
         use ap::Event::*;
         for event in self.take_events() {
-            if let DeathLink { source, time, .. } = event {
-                self.receive_death_link(source, time)
+            if let DeathLink {
+                source,
+                time,
+                cause,
+                ..
+            } = event
+            {
+                self.receive_death_link(source, time, cause)
             }
         }
 
@@ -104,19 +118,106 @@ impl shared::Core for Core {
         Ok(())
     }
 
+    fn death_link_status(&self) -> Option<DeathLinkStatus> {
+        let options = &self.client()?.slot_data().options;
+        Some(DeathLinkStatus {
+            mode: (&options.death_link).into(),
+            amnesty: options.death_link_amnesty,
+            deaths: SAVE_DATA.instance().map_or(0, |save| save.deaths),
+            grace_period: DEATH_LINK_GRACE_PERIOD
+                .checked_sub(self.last_death_link.elapsed())
+                .filter(|remaining| !remaining.is_zero()),
+            last_event: self.last_death_link_event.clone(),
+        })
+    }
+
+    fn save_store(&self) -> Option<&'static dyn DynSaveStore> {
+        Some(&SAVE_DATA)
+    }
+
+    fn slot_data_summary(&self) -> Option<String> {
+        let slot_data = self.client()?.slot_data();
+        Some(format!(
+            "Goal: {}\nItem mappings: {}\nItem counts: {}\nOptions: {:#?}\n",
+            slot_data
+                .goal
+                .iter()
+                .map(|flag| slot_data.goal_name(*flag))
+                .collect::<Vec<_>>()
+                .join(", "),
+            slot_data.ap_ids_to_item_ids.len(),
+            slot_data.item_counts.len(),
+            slot_data.options,
+        ))
+    }
+
+    fn goal_progress(&self) -> Option<Vec<GoalRequirement>> {
+        // Event flags are only meaningful once a save is loaded.
+        SAVE_DATA.instance()?;
+        let event_man = unsafe { SprjEventFlagMan::instance() }.ok()?;
+        let slot_data = self.client()?.slot_data();
+        Some(
+            slot_data
+                .goal
+                .iter()
+                .map(|flag| GoalRequirement {
+                    name: slot_data.goal_name(*flag),
+                    complete: event_man.get_flag(*flag),
+                })
+                .collect(),
+        )
+    }
+
     fn handle_command(&mut self, command: &str, arg: Option<&str>) -> bool {
         let mut arg_error = |usage: &str| {
             self.log(vec![
                 RichText::Color {
-                    text: format!("Invalid {}.", command),
+                    text: tr!("command-invalid", command = command),
                     color: ap::TextColor::Red,
                 },
-                " Usage:\n".into(),
-                usage.into(),
+                " ".into(),
+                tr!("command-usage", usage = usage).into(),
             ]);
         };
 
         match command {
+            "!goal" => {
+                let Some(requirements) = self.goal_progress() else {
+                    self.log(RichText::Color {
+                        text: tr!("goal-unavailable"),
+                        color: ap::TextColor::Red,
+                    });
+                    return true;
+                };
+
+                let complete = requirements.iter().filter(|r| r.complete).count();
+                let mut message: Vec<RichText> = vec![
+                    tr!(
+                        "goal-progress",
+                        complete = complete,
+                        total = requirements.len()
+                    )
+                    .into(),
+                ];
+                for requirement in requirements {
+                    message.push(format!("\n{}: ", requirement.name).into());
+                    message.push(if requirement.complete {
+                        RichText::Color {
+                            text: tr!("goal-defeated"),
+                            color: ap::TextColor::Green,
+                        }
+                    } else {
+                        RichText::Color {
+                            text: tr!("goal-remaining"),
+                            color: ap::TextColor::Red,
+                        }
+                    });
+                }
+                self.log(message);
+
+                true
+            }
+
             "!getevent" => {
                 let Some(flag) = arg.and_then(|f| u32::from_str(f).ok()) else {
                     arg_error("!getevent EVENT_FLAG");
@@ -125,7 +226,7 @@ impl shared::Core for Core {
 
                 let Ok(flag) = EventFlag::try_from(flag) else {
                     self.log(RichText::Color {
-                        text: format!("Invalid event ID: {}", flag),
+                        text: tr!("event-invalid", flag = flag),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -133,7 +234,7 @@ impl shared::Core for Core {
 
                 let Ok(events) = (unsafe { SprjEventFlagMan::instance() }) else {
                     self.log(RichText::Color {
-                        text: "SprjEventFlagMan not loaded".into(),
+                        text: tr!("event-flags-unavailable"),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -141,7 +242,7 @@ impl shared::Core for Core {
 
                 let value = events.get_flag(flag);
                 self.log(vec![
-                    "Event ".into(),
+                    format!("{} ", tr!("event-get")).into(),
                     RichText::Color {
                         // TODO: Use `u32::from()` once EventFlag supports it
                         text: format!("{:?}", unsafe { mem::transmute::<EventFlag, u32>(flag) }),
@@ -177,7 +278,7 @@ impl shared::Core for Core {
 
                 let Ok(flag) = EventFlag::try_from(flag) else {
                     self.log(RichText::Color {
-                        text: format!("Invalid event ID: {}", flag),
+                        text: tr!("event-invalid", flag = flag),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -185,7 +286,7 @@ impl shared::Core for Core {
 
                 let Ok(events) = (unsafe { SprjEventFlagMan::instance() }) else {
                     self.log(RichText::Color {
-                        text: "SprjEventFlagMan not loaded".into(),
+                        text: tr!("event-flags-unavailable"),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -193,13 +294,13 @@ impl shared::Core for Core {
 
                 events.set_flag(flag, value);
                 self.log(vec![
-                    "Set event ".into(),
+                    format!("{} ", tr!("event-set")).into(),
                     RichText::Color {
                         // TODO: Use `u32::from()` once EventFlag supports it
                         text: format!("{:?}", unsafe { mem::transmute::<EventFlag, u32>(flag) }),
                         color: ap::TextColor::Blue,
                     },
-                    " to ".into(),
+                    format!(" {} ", tr!("event-set-to")).into(),
                     RichText::Color {
                         text: format!("{:?}", value),
                         color: if value {
@@ -224,37 +325,28 @@ impl Core {
     /// data's notion based on whatever is available if it doesn't exist yet.
     fn check_seed_conflict(&mut self) -> Result<()> {
         let client_seed = self.client().map(|c| c.seed_name());
-        let save = SaveData::instance();
+        let save = SAVE_DATA.instance();
         let save_seed = save.as_ref().and_then(|s| s.seed.as_ref());
 
+        let randomizer = GameType::DarkSoulsIII.static_randomizer_basename();
         match (client_seed, save_seed) {
-            (Some(client_seed), _) if client_seed != self.seed() => bail!(
-                "You've connected to a different Archipelago multiworld than the one that \
-                 DS3Randomizer.exe used!\n\
-                 \n\
-		 Connected room seed: {}\n\
-                 DS3Randomizer.exe seed: {}",
-                client_seed,
-                self.seed()
-            ),
-            (Some(client_seed), Some(save_seed)) if client_seed != save_seed => bail!(
-                "You've connected to a different Archipelago multiworld than the one that \
-                 you used before with this save!\n\
-                 \n\
-		 Connected room seed: {}\n\
-		 Save file seed: {}",
-                client_seed,
-                save_seed
-            ),
-            (_, Some(save_seed)) if self.seed() != save_seed => bail!(
-                "Your most recent DS3Randomizer.exe invocation connected to a different \
-                 Archipealgo multiworld than the one that you used before with this save!\n\
-                 \n\
-                 DS3Randomizer.exe seed: {}\n\
-                 Save file seed: {}",
-                self.seed(),
-                save_seed
-            ),
+            (Some(client_seed), _) if client_seed != self.seed() => bail!(tr!(
+                "seed-conflict-room-randomizer",
+                randomizer = randomizer,
+                room_seed = client_seed,
+                randomizer_seed = self.seed(),
+            )),
+            (Some(client_seed), Some(save_seed)) if client_seed != save_seed => bail!(tr!(
+                "seed-conflict-room-save",
+                room_seed = client_seed,
+                save_seed = save_seed,
+            )),
+            (_, Some(save_seed)) if self.seed() != save_seed => bail!(tr!(
+                "seed-conflict-randomizer-save",
+                randomizer = randomizer,
+                randomizer_seed = self.seed(),
+                save_seed = save_seed,
+            )),
             _ => Ok(()),
         }
     }
@@ -288,7 +380,7 @@ impl Core {
     }
 
     /// Handle new items, distributing them to the player when appropriate. This
-    /// also initializes the [SaveData] for a new file.
+    /// also initializes the [SAVE_DATA] for a new file.
     fn process_incoming_items(&mut self) {
         let Some(client) = self.client() else {
             return;
@@ -299,7 +391,7 @@ impl Core {
         let Ok(player_game_data) = (unsafe { PlayerGameData::instance() }) else {
             return;
         };
-        let mut save_data = SaveData::instance_mut();
+        let mut save_data = SAVE_DATA.instance_mut();
         let Some(save_data) = save_data.as_mut() else {
             return;
         };
@@ -340,6 +432,15 @@ impl Core {
                 ds3_id,
                 item.location().name()
             );
+            let event = GameEvent::ItemGranted {
+                index: item.index(),
+                item_id: item.item().id(),
+                item_name: item.item().name().to_string(),
+                game_item_id: format!("{:?}", ds3_id),
+                quantity,
+                location_id: item.location().id(),
+                sender: item.sender().name().to_string(),
+            };
 
             // Grant Path of the Dragon as a gesture rather than an item.
             if ds3_id.category() == ItemCategory::Goods && ds3_id.param_id() == 9030 {
@@ -354,13 +455,14 @@ impl Core {
 
             save_data.items_granted += 1;
             self.last_item_time = Instant::now();
+            self.record_event(event);
         }
     }
 
     /// Removes any placeholder items from the player's inventory and notifies
     /// the server that they've been accessed.
     fn process_inventory_items(&mut self) -> Result<()> {
-        let Some(ref mut save_data) = SaveData::instance_mut() else {
+        let Some(ref mut save_data) = SAVE_DATA.instance_mut() else {
             return Ok(());
         };
         let Ok(game_data_man) = (unsafe { GameDataMan::instance() }) else {
@@ -392,7 +494,11 @@ impl Core {
             let row = row.as_dyn();
 
             info!("  Archipelago location: {}", row.archipelago_location_id());
-            save_data.locations.insert(row.archipelago_location_id());
+            if save_data.locations.insert(row.archipelago_location_id()) {
+                self.record_event(GameEvent::LocationChecked {
+                    location_id: row.archipelago_location_id(),
+                });
+            }
 
             if let EquipParamStruct::EQUIP_PARAM_GOODS_ST(good) = row.as_enum()
                 && good.icon_id() == 7039
@@ -438,7 +544,7 @@ impl Core {
     }
 
     /// Kills the player after a death link is received.
-    fn receive_death_link(&mut self, source: String, time: SystemTime) {
+    fn receive_death_link(&mut self, source: String, time: SystemTime, cause: Option<String>) {
         if !self.allow_death_link() {
             return;
         }
@@ -464,6 +570,16 @@ impl Core {
         // Always ignore death links that we sent.
         player.kill();
         self.last_death_link = Instant::now();
+        self.record_event(GameEvent::DeathLinkReceived {
+            source: source.clone(),
+            cause: cause.clone(),
+        });
+        self.last_death_link_event = Some(DeathLinkEvent {
+            direction: DeathLinkDirection::Received,
+            source,
+            cause,
+            time: self.last_death_link,
+        });
     }
 
     /// If a shop is currently open, send all its locations as hints to the
@@ -516,7 +632,7 @@ impl Core {
         if player.super_chr_ins.modules.data.hp != 0 {
             return Ok(());
         }
-        let Some(mut save) = SaveData::instance_mut() else {
+        let Some(mut save) = SAVE_DATA.instance_mut() else {
             return Ok(());
         };
 
@@ -528,17 +644,23 @@ impl Core {
             if save.deaths >= amnesty {
                 client.death_link(Default::default())?;
                 save.deaths = 0;
-                self.log("You have sent a death link to your teammates.");
+                let source = client.this_player().name().to_string();
+                self.last_death_link_event = Some(DeathLinkEvent {
+                    direction: DeathLinkDirection::Sent,
+                    source,
+                    cause: None,
+                    time: Instant::now(),
+                });
+                self.record_event(GameEvent::DeathLinkSent);
+                self.log(tr!("death-link-sent"));
             } else {
                 let remaining = amnesty - save.deaths;
-                self.log(format!(
-                    "You have been granted death link amnesty. {}",
-                    if remaining == 1 {
-                        "1 death remains.".to_string()
-                    } else {
-                        format!("{} deaths remain.", remaining)
-                    }
-                ));
+                self.record_event(GameEvent::DeathLinkAmnesty { remaining });
+                self.log(if remaining == 1 {
+                    tr!("death-link-amnesty-granted-one")
+                } else {
+                    tr!("death-link-amnesty-granted", remaining = remaining)
+                });
             }
         }
 
@@ -574,6 +696,7 @@ impl Core {
         {
             client.set_status(ap::ClientStatus::Goal)?;
             self.sent_goal = true;
+            self.record_event(GameEvent::GoalCompleted);
         }
 
         Ok(())
@@ -5,7 +5,7 @@ use fromsoftware_shared::FromStatic;
 use ilhook::x64::*;
 use log::*;
 
-use crate::save_data::SaveData;
+use crate::save_data::SAVE_DATA;
 
 /// Establishes hooks which ensure the items (which may be placeholders encoding
 /// information relevant to Archipelago) are replaced by those which are correct
@@ -13,7 +13,8 @@ use crate::save_data::SaveData;
 pub unsafe fn hook_items() {
     let callback = |reg: *mut Registers| {
         let items = unsafe { &mut *((*reg).rdx as *mut ItemBuffer) };
-        on_grant_items(items);
+        // If this panics, the items are left as-is.
+        shared::catch_hook_panic("the item grant hook", || on_grant_items(items));
     };
     std::mem::forget(
         unsafe {
@@ -47,7 +48,7 @@ fn on_grant_items(items: &mut ItemBuffer) {
             info!("  Archipelago location: {}", row.archipelago_location_id());
             info!("  Converting to {}x {:?}", quantity, real_id);
 
-            if let Some(ref mut save_data) = SaveData::instance_mut() {
+            if let Some(ref mut save_data) = SAVE_DATA.instance_mut() {
                 // Save data *should* always be loaded when the player gets an
                 // item, but there's no need to crash if it's not.
                 save_data.locations.insert(row.archipelago_location_id());
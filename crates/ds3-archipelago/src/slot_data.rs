@@ -1,4 +1,4 @@
-use std::{collections::HashMap, hash::Hash, str::FromStr};
+use std::{collections::HashMap, hash::Hash, mem, str::FromStr};
 
 use darksouls3::sprj::{EventFlag, ItemId};
 use serde::de::{Error, Unexpected};
@@ -16,6 +16,11 @@ pub struct SlotData {
     #[serde(deserialize_with = "deserialize_goal")]
     pub goal: Vec<EventFlag>,
 
+    /// A map from the event flags in [goal] to human-readable names for the
+    /// conditions they represent, such as the names of bosses to defeat.
+    #[serde(default = "default_goal_names")]
+    pub goal_names: HashMap<I64Key, String>,
+
     /// A map from Archipelago's item IDs to DS3's.
     pub ap_ids_to_item_ids: HashMap<I64Key, DeserializableItemId>,
 
@@ -27,6 +32,19 @@ pub struct SlotData {
     pub options: Options,
 }
 
+impl SlotData {
+    /// Returns the human-readable name for [flag], which should be one of the
+    /// flags in [goal].
+    pub fn goal_name(&self, flag: EventFlag) -> String {
+        // TODO: Use `u32::from()` once EventFlag supports it
+        let id = unsafe { mem::transmute::<EventFlag, u32>(flag) };
+        self.goal_names
+            .get(&I64Key(id.into()))
+            .cloned()
+            .unwrap_or_else(|| format!("Event {id}"))
+    }
+}
+
 /// Deserializes a list of event flags, defaulting to the flag for defeating
 /// Soul of Cinder.
 fn deserialize_goal<'de, D: Deserializer<'de>>(
@@ -48,6 +66,12 @@ fn default_goal() -> Vec<EventFlag> {
     vec![14100800.try_into().unwrap()]
 }
 
+/// The default goal names, used because older versions of the DS3 AP world
+/// don't provide them.
+fn default_goal_names() -> HashMap<I64Key, String> {
+    HashMap::from([(I64Key(14100800), "Soul of Cinder".to_string())])
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Options {
     /// Whether to kill the player when other players are killed and vice versa.
@@ -90,6 +114,16 @@ pub enum DeathLinkOption {
     LostSouls = 2,
 }
 
+impl From<&DeathLinkOption> for shared::DeathLinkMode {
+    fn from(option: &DeathLinkOption) -> Self {
+        match option {
+            DeathLinkOption::Off => shared::DeathLinkMode::Off,
+            DeathLinkOption::AnyDeath => shared::DeathLinkMode::AnyDeath,
+            DeathLinkOption::LostSouls => shared::DeathLinkMode::LostSouls,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Hash, PartialEq, Eq)]
 #[serde(try_from = "&str")]
 #[repr(transparent)]
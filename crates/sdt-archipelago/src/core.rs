@@ -11,7 +11,7 @@ use sekiro::sprj::*;
 use crate::item::{EquipParamExt, ItemIdExt};
 use crate::save_data::*;
 use crate::slot_data::{I64Key, SlotData};
-use shared::{Core as SharedCore, CoreBase};
+use shared::{Core as SharedCore, CoreBase, DynSaveStore, GameEvent, GameType, tr};
 
 /// The core of the Archipelago mod. This is responsible for running the
 /// non-UI-related game logic and interacting with the Archipelago client.
@@ -57,12 +57,25 @@ impl shared::Core for Core {
         &mut self.base
     }
 
+    fn save_store(&self) -> Option<&'static dyn DynSaveStore> {
+        Some(&SAVE_DATA)
+    }
+
+    fn slot_data_summary(&self) -> Option<String> {
+        let slot_data = self.client()?.slot_data();
+        Some(format!(
+            "Item mappings: {}\nItem counts: {}\n",
+            slot_data.ap_ids_to_item_ids.len(),
+            slot_data.item_counts.len(),
+        ))
+    }
+
     /// Updates the game logic and checks for common errors. This does nothing
     /// if we're not currently connected to the Archipelago server or if the mod
     /// has encountered a fatal error.
     fn update_live(&mut self) -> Result<()> {
         self.check_seed_conflict()?;
-        if let Some(save_data) = SaveData::instance_mut().as_mut()
+        if let Some(save_data) = SAVE_DATA.instance_mut().as_mut()
             && save_data.seed.is_none()
         {
             save_data.seed = Some(self.seed().to_string());
@@ -83,11 +96,11 @@ impl shared::Core for Core {
         let mut arg_error = |usage: &str| {
             self.log(vec![
                 RichText::Color {
-                    text: format!("Invalid {}.", command),
+                    text: tr!("command-invalid", command = command),
                     color: ap::TextColor::Red,
                 },
-                " Usage:\n".into(),
-                usage.into(),
+                " ".into(),
+                tr!("command-usage", usage = usage).into(),
             ]);
         };
 
@@ -100,7 +113,7 @@ impl shared::Core for Core {
 
                 let Ok(flag) = EventFlag::try_from(flag) else {
                     self.log(RichText::Color {
-                        text: format!("Invalid event ID: {}", flag),
+                        text: tr!("event-invalid", flag = flag),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -108,7 +121,7 @@ impl shared::Core for Core {
 
                 let Ok(events) = (unsafe { SprjEventFlagMan::instance() }) else {
                     self.log(RichText::Color {
-                        text: "SprjEventFlagMan not loaded".into(),
+                        text: tr!("event-flags-unavailable"),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -116,7 +129,7 @@ impl shared::Core for Core {
 
                 let value = events.get_flag(flag);
                 self.log(vec![
-                    "Event ".into(),
+                    format!("{} ", tr!("event-get")).into(),
                     RichText::Color {
                         text: format!("{:?}", u32::from(flag)),
                         color: ap::TextColor::Blue,
@@ -151,7 +164,7 @@ impl shared::Core for Core {
 
                 let Ok(flag) = EventFlag::try_from(flag) else {
                     self.log(RichText::Color {
-                        text: format!("Invalid event ID: {}", flag),
+                        text: tr!("event-invalid", flag = flag),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -159,7 +172,7 @@ impl shared::Core for Core {
 
                 let Ok(events) = (unsafe { SprjEventFlagMan::instance() }) else {
                     self.log(RichText::Color {
-                        text: "SprjEventFlagMan not loaded".into(),
+                        text: tr!("event-flags-unavailable"),
                         color: ap::TextColor::Red,
                     });
                     return true;
@@ -167,12 +180,12 @@ impl shared::Core for Core {
 
                 events.set_flag(flag, value);
                 self.log(vec![
-                    "Set event ".into(),
+                    format!("{} ", tr!("event-set")).into(),
                     RichText::Color {
                         text: format!("{:?}", u32::from(flag)),
                         color: ap::TextColor::Blue,
                     },
-                    " to ".into(),
+                    format!(" {} ", tr!("event-set-to")).into(),
                     RichText::Color {
                         text: format!("{:?}", value),
                         color: if value {
@@ -197,43 +210,34 @@ impl Core {
     /// data's notion based on whatever is available if it doesn't exist yet.
     fn check_seed_conflict(&mut self) -> Result<()> {
         let client_seed = self.client().map(|c| c.seed_name());
-        let save = SaveData::instance();
+        let save = SAVE_DATA.instance();
         let save_seed = save.as_ref().and_then(|s| s.seed.as_ref());
 
+        let randomizer = GameType::Sekiro.static_randomizer_basename();
         match (client_seed, save_seed) {
-            (Some(client_seed), _) if client_seed != self.seed() => bail!(
-                "You've connected to a different Archipelago multiworld than the one that \
-                 SekiroRandomizer.exe used!\n\
-                 \n\
-		 Connected room seed: {}\n\
-                 SekiroRandomizer.exe seed: {}",
-                client_seed,
-                self.seed()
-            ),
-            (Some(client_seed), Some(save_seed)) if client_seed != save_seed => bail!(
-                "You've connected to a different Archipelago multiworld than the one that \
-                 you used before with this save!\n\
-                 \n\
-		 Connected room seed: {}\n\
-		 Save file seed: {}",
-                client_seed,
-                save_seed
-            ),
-            (_, Some(save_seed)) if self.seed() != save_seed => bail!(
-                "Your most recent SekiroRandomizer.exe invocation connected to a different \
-                 Archipealgo multiworld than the one that you used before with this save!\n\
-                 \n\
-                 SekiroRandomizer.exe seed: {}\n\
-                 Save file seed: {}",
-                self.seed(),
-                save_seed
-            ),
+            (Some(client_seed), _) if client_seed != self.seed() => bail!(tr!(
+                "seed-conflict-room-randomizer",
+                randomizer = randomizer,
+                room_seed = client_seed,
+                randomizer_seed = self.seed(),
+            )),
+            (Some(client_seed), Some(save_seed)) if client_seed != save_seed => bail!(tr!(
+                "seed-conflict-room-save",
+                room_seed = client_seed,
+                save_seed = save_seed,
+            )),
+            (_, Some(save_seed)) if self.seed() != save_seed => bail!(tr!(
+                "seed-conflict-randomizer-save",
+                randomizer = randomizer,
+                randomizer_seed = self.seed(),
+                save_seed = save_seed,
+            )),
             _ => Ok(()),
         }
     }
 
     /// Handle new items, distributing them to the player when appropriate. This
-    /// also initializes the [SaveData] for a new file.
+    /// also initializes the [SAVE_DATA] for a new file.
     fn process_incoming_items(&mut self) {
         let Some(client) = self.client() else {
             return;
@@ -241,7 +245,7 @@ impl Core {
         let Ok(item_man) = (unsafe { MapItemMan::instance() }) else {
             return;
         };
-        let mut save_data = SaveData::instance_mut();
+        let mut save_data = SAVE_DATA.instance_mut();
         let Some(save_data) = save_data.as_mut() else {
             return;
         };
@@ -282,18 +286,28 @@ impl Core {
                 sdt_id,
                 item.location().name()
             );
+            let event = GameEvent::ItemGranted {
+                index: item.index(),
+                item_id: item.item().id(),
+                item_name: item.item().name().to_string(),
+                game_item_id: format!("{:?}", sdt_id),
+                quantity,
+                location_id: item.location().id(),
+                sender: item.sender().name().to_string(),
+            };
 
             item_man.grant_item(ItemBufferEntry::new(sdt_id, quantity));
 
             save_data.items_granted += 1;
             self.last_item_time = Instant::now();
+            self.record_event(event);
         }
     }
 
     /// Removes any placeholder items from the player's inventory and notifies
     /// the server that they've been accessed.
     fn process_inventory_items(&mut self) -> Result<()> {
-        let Some(ref mut save_data) = SaveData::instance_mut() else {
+        let Some(ref mut save_data) = SAVE_DATA.instance_mut() else {
             return Ok(());
         };
         let Ok(game_data_man) = (unsafe { GameDataMan::instance() }) else {
@@ -325,7 +339,11 @@ impl Core {
             let row = row.as_dyn();
 
             info!("  Archipelago location: {}", row.archipelago_location_id());
-            save_data.locations.insert(row.archipelago_location_id());
+            if save_data.locations.insert(row.archipelago_location_id()) {
+                self.record_event(GameEvent::LocationChecked {
+                    location_id: row.archipelago_location_id(),
+                });
+            }
 
             if let Some((real_id, quantity)) = row.archipelago_item() {
                 info!("  Converting to {}x {:?}", quantity, real_id);
@@ -365,6 +383,7 @@ impl Core {
         {
             client.set_status(ap::ClientStatus::Goal)?;
             self.sent_goal = true;
+            self.record_event(GameEvent::GoalCompleted);
         }
 
         Ok(())
@@ -6,7 +6,7 @@ mod item;
 mod save_data;
 mod slot_data;
 
-use save_data::SaveData;
+use save_data::SAVE_DATA;
 
 /// The entrypoint called when the DLL is first loaded.
 ///
@@ -26,7 +26,7 @@ extern "C" fn DllMain(_: HINSTANCE, call_reason: u32) -> bool {
 
     // Safety: We only hook these functions here specifically.
     unsafe {
-        SaveData::hook();
+        SAVE_DATA.hook();
         item::hook_items();
     }
 
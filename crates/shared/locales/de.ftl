@@ -0,0 +1,230 @@
+# German messages for the Archipelago client. Any message missing here falls
+# back to English.
+
+## Connection status
+
+status-connected = Verbunden
+status-connecting = Verbinde...
+status-disconnected = Getrennt
+reconnecting = Verbinde erneut...
+connection-refused = Verbindung abgelehnt.
+connection-refused-hint = Stelle sicher, dass die Server-Sitzung läuft und die URL aktuell ist.
+connection-failed = Verbindung fehlgeschlagen:
+disconnected = Getrennt:
+
+## Fatal errors
+
+version-conflict =
+    Deine apconfig.json wurde mit dem statischen Randomizer v{ $config_version } erstellt, aber dieser Client ist v{ $client_version }. Führe den statischen Randomizer mit der aktuellen Version erneut aus.
+seed-conflict-room-randomizer =
+    Du hast dich mit einer anderen Archipelago-Multiworld verbunden als der, die { $randomizer } verwendet hat!
+
+    Seed des verbundenen Raums: { $room_seed }
+    Seed von { $randomizer }: { $randomizer_seed }
+seed-conflict-room-save =
+    Du hast dich mit einer anderen Archipelago-Multiworld verbunden als der, die du zuvor mit diesem Spielstand verwendet hast!
+
+    Seed des verbundenen Raums: { $room_seed }
+    Seed des Spielstands: { $save_seed }
+seed-conflict-randomizer-save =
+    Dein letzter Aufruf von { $randomizer } hat sich mit einer anderen Archipelago-Multiworld verbunden als der, die du zuvor mit diesem Spielstand verwendet hast!
+
+    Seed von { $randomizer }: { $randomizer_seed }
+    Seed des Spielstands: { $save_seed }
+save-data-too-new =
+    Dieser Spielstand wurde zuletzt mit einer neueren Version des Archipelago-Clients gespielt, die ihre Daten im Format v{ $version } speichert. Dieser Client versteht nur bis v{ $supported }. Aktualisiere den Client, um mit diesem Spielstand weiterzuspielen. Seine Archipelago-Daten bleiben bis dahin unverändert.
+
+## Overlay
+
+overlay-title = Archipelago-Client { $version } [{ $status }]
+input-title = Archipelago-Chat
+menu-settings = Einstellungen
+room-url = Raum-URL
+connect = Verbinden
+reconnect = Erneut verbinden
+change-url = URL ändern
+
+## Settings
+
+settings-title = Archipelago-Overlay-Einstellungen
+settings-font-size = Schriftgröße
+settings-unfocused-opacity = Deckkraft ohne Fokus
+settings-language = Sprache
+settings-color-theme = Farbschema
+settings-item-icons = Gegenstandssymbole
+settings-gamepad-hint = Controller: Drücke Back + R3, um das Overlay zu betreten oder zu verlassen. Scrolle das Protokoll mit dem rechten Stick.
+settings-item-icons-tooltip = Zeigt ein Symbol vor Fortschritts-, nützlichen und Fallen-Gegenständen
+settings-reset-colors = Eigene Farben zurücksetzen
+settings-reset-layout = Layout zurücksetzen
+settings-reset-layout-tooltip = Löst alle Overlay-Fenster aus dem Dock und setzt sie an ihre Standardpositionen zurück
+settings-ok = Ok
+
+theme-default = Standard
+theme-deuteranopia = Deuteranopie
+theme-protanopia = Protanopie
+theme-high-contrast = Hoher Kontrast
+theme-custom = Eigenes
+
+color-player = Spieler
+color-progression-item = Fortschritts-Gegenstand
+color-useful-item = Nützlicher Gegenstand
+color-trap-item = Fallen-Gegenstand
+color-filler-item = Füll-Gegenstand
+color-location = Ort
+color-black = Schwarz
+color-red = Rot
+color-green = Grün
+color-yellow = Gelb
+color-blue = Blau
+color-magenta = Magenta
+color-cyan = Cyan
+color-white = Weiß
+
+## Death link
+
+death-link-off = Death Link: Aus
+death-link-any-death = Death Link: Jeder Tod
+death-link-lost-souls = Death Link: Verlorene Seelen
+death-link-amnesty = Amnestie { $remaining }/{ $total }
+death-link-grace = Schonfrist { $seconds }s
+death-link-none-yet = In dieser Sitzung gab es noch keine Death Links.
+death-link-last-sent = Du hast vor { $seconds }s einen Death Link gesendet.
+death-link-last-received = { $player } hat dich vor { $seconds }s getötet.
+death-link-sent = Du hast deinen Teamkameraden einen Death Link gesendet.
+death-link-amnesty-granted-one = Dir wurde Death-Link-Amnestie gewährt. 1 Tod verbleibt.
+death-link-amnesty-granted = Dir wurde Death-Link-Amnestie gewährt. { $remaining } Tode verbleiben.
+
+## Goal
+
+goal-menu = Ziel { $complete }/{ $total }
+goal-defeated = Besiegt
+goal-remaining = Verbleibend
+goal-progress = Zielfortschritt: { $complete }/{ $total }
+goal-unavailable = Der Zielfortschritt ist nur verfügbar, während ein Spielstand geladen ist.
+
+## Log links
+
+item-class-progression = Fortschritt
+item-class-useful = Nützlich
+item-class-trap = Falle
+item-class-filler = Füller
+link-player-game = Spielt { $game }
+link-item-owner = Aus der Welt von { $owner }
+link-location-owner = In der Welt von { $owner }
+link-hint = Hinweis für diesen Gegenstand
+link-copy = Namen kopieren
+link-filter = Log nach diesem Spieler filtern
+log-clear-filter = Zeige { $player } (zurücksetzen)
+
+## Profiler
+
+menu-profiler = Profiler
+profiler-title = Profiler
+profiler-pause = Pausieren
+profiler-resume = Fortsetzen
+profiler-reset = Zurücksetzen
+profiler-frame = Frame: { $milliseconds } ms ({ $fps } FPS)
+profiler-section = Abschnitt
+profiler-average = Ø/Frame
+profiler-share = Anteil am Frame
+profiler-graph = Verlauf
+profiler-capture-trace = Trace aufzeichnen
+profiler-capture-trace-tooltip = Die Zeit jedes Abschnitts aufzeichnen, um Ruckler in Perfetto zu untersuchen
+profiler-save-trace = Letzte 10 s speichern
+profiler-budget = Budget (ms)
+profiler-budget-tooltip = Eine Warnung mit dem langsamsten Abschnitt protokollieren, wenn die Arbeit der Mod in einem Frame länger dauert, höchstens alle 10 Sekunden. 0 deaktiviert die Warnung.
+profiler-stats =
+    Seit dem letzten Bericht (ms):
+    min { $min } | p50 { $p50 } | p95 { $p95 } | p99 { $p99 } | max { $max }
+
+## Commands
+
+command-invalid = Ungültiges { $command }.
+command-usage =
+    Verwendung:
+    { $usage }
+event-invalid = Ungültige Event-ID: { $flag }
+event-flags-unavailable = Event-Flags sind noch nicht geladen.
+event-get = Event
+event-set = Setze Event
+event-set-to = auf
+trace-started = Trace wird aufgezeichnet. Führe !trace SEKUNDEN aus, um ihn zu speichern.
+trace-stopped = Trace-Aufzeichnung beendet.
+trace-invalid = Ungültiges !trace.
+trace-usage =
+    Verwendung:
+    !trace start
+    !trace stop
+    !trace [SEKUNDEN]
+trace-saved = Trace gespeichert unter { $path }
+trace-failed = Trace konnte nicht gespeichert werden: { $error }
+trace-unavailable = Tracing erfordert einen Build mit dem Feature profile.
+loglevel-current =
+    Log-Level der Datei: { $file }
+    Log-Level des Terminals: { $terminal }
+loglevel-usage =
+    Verwendung:
+    !loglevel DIREKTIVEN
+    !loglevel terminal DIREKTIVEN
+    Zum Beispiel: !loglevel info,ds3_archipelago::item=debug,tungstenite=off
+loglevel-set = Log-Level der Datei auf { $level } gesetzt
+loglevel-terminal-set = Log-Level des Terminals auf { $level } gesetzt
+loglevel-invalid = Ungültiges !loglevel: { $error }
+savedata-usage =
+    Verwendung:
+    !savedata export
+    !savedata import
+    !savedata import apply
+
+    Ein Export schreibt deine Archipelago-Spielstanddaten in die Datei savedata.json im Mod-Verzeichnis. Ein Import zeigt, was sich ändern würde, bevor du ihn anwendest.
+savedata-failed = !savedata fehlgeschlagen: { $error }
+savedata-not-loaded = Spielstanddaten sind nur verfügbar, während ein Spielstand geladen ist
+savedata-unsupported = Dieses Spiel unterstützt keinen Import von Spielstanddaten
+savedata-exported = Spielstanddaten nach { $path } exportiert. Bearbeite die Datei und führe !savedata import aus, um sie wieder zu laden.
+savedata-read-failed = { $path } konnte nicht gelesen werden
+savedata-invalid-json = Ungültiges JSON
+savedata-unchanged = Die importierten Spielstanddaten stimmen mit den aktuellen überein. Es gibt nichts zu tun.
+savedata-diff =
+    Der Import von { $path } würde Folgendes ändern:
+    { $changes }
+    Führe !savedata import apply aus, um die Änderungen anzuwenden.
+savedata-diff-added = { $path }: { $entries } hinzugefügt
+savedata-diff-removed = { $path }: { $entries } entfernt
+savedata-diff-changed = { $path }: { $old } -> { $new }
+savedata-entries = { $count } Einträge
+savedata-no-pending = Führe zuerst !savedata import aus, um die Änderungen zu prüfen
+savedata-changed = Deine Spielstanddaten haben sich seit der Prüfung des Imports geändert. Führe !savedata import erneut aus
+savedata-imported = Spielstanddaten importiert.
+savedata-too-many-items = items_granted ist { $items_granted }, aber der Server hat erst { $received } Gegenstände gesendet
+savedata-seed-mismatch = Der importierte Seed { $seed } stimmt nicht mit dem Seed { $room_seed } des verbundenen Raums überein
+
+## Save backups
+
+save-data-invalid =
+    Deine Archipelago-Spielstanddaten konnten nicht gelesen werden: { $error }
+save-data-went-backwards =
+    Deine Archipelago-Spielstanddaten scheinen älter als deine neueste Sicherung zu sein. Sie enthalten { $items_granted } erhaltene Gegenstände und { $locations } geprüfte Orte, die Sicherung aber { $backup_items_granted } und { $backup_locations }.
+save-data-corrupt =
+    Deine Archipelago-Spielstanddaten haben die Integritätsprüfung nicht bestanden und wurden daher ignoriert. Sie wurden möglicherweise beschädigt oder von einem anderen Mod überschrieben. { $error } Sicherungen für diesen Seed und Slot liegen in { $backups }, in einem Ordner pro Charakter.
+save-data-checksum-mismatch = Die gespeicherte Prüfsumme ist { $expected }, aber die Prüfsumme der Daten ist { $actual }.
+backup-info = Neueste Sicherung: { $path } ({ $items_granted } erhaltene Gegenstände, { $locations } geprüfte Orte)
+backup-info-any-character = Neueste Sicherung für diesen Seed und Slot: { $path } ({ $items_granted } erhaltene Gegenstände, { $locations } geprüfte Orte). Die geladenen Spielstanddaten verraten nicht, zu welchem Charakter sie gehören, daher kann diese Sicherung von einem anderen Charakter stammen.
+no-backup = Für diesen Charakter gibt es keine Sicherung.
+no-backup-any-character = Für diesen Seed und Slot gibt es keine Sicherungen.
+restore-backup = Sicherung wiederherstellen
+dismiss = Schließen
+dismiss-discards-save = Beim Schließen werden die Spielstanddaten verworfen, die nicht gelesen werden konnten. Die Mod beginnt für diesen Spielstand mit leeren Daten, sodass der Server dir alle bereits erhaltenen Gegenstände erneut schickt. Stelle nach Möglichkeit stattdessen eine Sicherung wieder her.
+dismiss-anyway = Trotzdem schließen
+cancel = Abbrechen
+restore-failed = Sicherung konnte nicht wiederhergestellt werden: { $error }
+
+## Error display
+
+show-full-error = Vollständigen Fehler anzeigen
+copy-error = Fehler kopieren
+open-log-folder = Log-Ordner öffnen
+retry = Erneut versuchen
+save-diagnostics = Diagnose speichern
+diagnostics-saved = Diagnose gespeichert unter { $path }. Bitte hänge diese Datei an deinen Fehlerbericht an.
+diagnostics-failed = Diagnose konnte nicht gespeichert werden: { $error }
+exit = Beenden
@@ -0,0 +1,233 @@
+# English messages for the Archipelago client. This is the fallback for every
+# other locale, so it must contain every message the client uses.
+#
+# Line breaks within a message are preserved, so keep each paragraph on a
+# single line and let the UI wrap it.
+
+## Connection status
+
+status-connected = Connected
+status-connecting = Connecting...
+status-disconnected = Disconnected
+reconnecting = Reconnecting...
+connection-refused = Connection refused.
+connection-refused-hint = Make sure the server session is running and the URL is up-to-date.
+connection-failed = Connection failed:
+disconnected = Disconnected:
+
+## Fatal errors
+
+version-conflict =
+    Your apconfig.json was generated using static randomizer v{ $config_version }, but this client is v{ $client_version }. Re-run the static randomizer with the current version.
+seed-conflict-room-randomizer =
+    You've connected to a different Archipelago multiworld than the one that { $randomizer } used!
+
+    Connected room seed: { $room_seed }
+    { $randomizer } seed: { $randomizer_seed }
+seed-conflict-room-save =
+    You've connected to a different Archipelago multiworld than the one that you used before with this save!
+
+    Connected room seed: { $room_seed }
+    Save file seed: { $save_seed }
+seed-conflict-randomizer-save =
+    Your most recent { $randomizer } invocation connected to a different Archipelago multiworld than the one that you used before with this save!
+
+    { $randomizer } seed: { $randomizer_seed }
+    Save file seed: { $save_seed }
+save-data-too-new =
+    This save file was last played with a newer version of the Archipelago client, which stores its data in format v{ $version }. This client only understands up to v{ $supported }. Update the client to keep playing this save. Its Archipelago data will be left untouched in the meantime.
+
+## Overlay
+
+overlay-title = Archipelago Client { $version } [{ $status }]
+input-title = Archipelago Chat
+menu-settings = Settings
+room-url = Room URL
+connect = Connect
+reconnect = Reconnect
+change-url = Change URL
+
+## Settings
+
+settings-title = Archipelago Overlay Settings
+settings-font-size = Font Size
+settings-unfocused-opacity = Unfocused Opacity
+settings-language = Language
+settings-color-theme = Color Theme
+settings-item-icons = Item Icons
+settings-gamepad-hint = Controller: press Back + R3 to enter or leave the overlay. Use the right stick to scroll the log.
+settings-item-icons-tooltip = Show an icon before progression, useful, and trap items
+settings-reset-colors = Reset Custom Colors
+settings-reset-layout = Reset Layout
+settings-reset-layout-tooltip = Undock all overlay windows and move them back to their default positions
+settings-ok = Ok
+
+theme-default = Default
+theme-deuteranopia = Deuteranopia
+theme-protanopia = Protanopia
+theme-high-contrast = High contrast
+theme-custom = Custom
+
+color-player = Player
+color-progression-item = Progression Item
+color-useful-item = Useful Item
+color-trap-item = Trap Item
+color-filler-item = Filler Item
+color-location = Location
+color-black = Black
+color-red = Red
+color-green = Green
+color-yellow = Yellow
+color-blue = Blue
+color-magenta = Magenta
+color-cyan = Cyan
+color-white = White
+
+## Death link
+
+death-link-off = Death link: Off
+death-link-any-death = Death link: Any death
+death-link-lost-souls = Death link: Lost souls
+death-link-amnesty = Amnesty { $remaining }/{ $total }
+death-link-grace = Grace { $seconds }s
+death-link-none-yet = No death links yet this session.
+death-link-last-sent = You sent a death link { $seconds }s ago.
+death-link-last-received = { $player } killed you { $seconds }s ago.
+death-link-sent = You have sent a death link to your teammates.
+death-link-amnesty-granted-one = You have been granted death link amnesty. 1 death remains.
+death-link-amnesty-granted = You have been granted death link amnesty. { $remaining } deaths remain.
+
+## Goal
+
+goal-menu = Goal { $complete }/{ $total }
+goal-defeated = Defeated
+goal-remaining = Remaining
+goal-progress = Goal progress: { $complete }/{ $total }
+goal-unavailable = Goal progress is only available while a save is loaded.
+
+## Log links
+
+item-class-progression = Progression
+item-class-useful = Useful
+item-class-trap = Trap
+item-class-filler = Filler
+link-player-game = Playing { $game }
+link-item-owner = From { $owner }'s world
+link-location-owner = In { $owner }'s world
+link-hint = Hint this item
+link-copy = Copy name
+link-filter = Filter log by this player
+log-clear-filter = Showing { $player } (clear)
+
+## Profiler
+
+menu-profiler = Profiler
+profiler-title = Profiler
+profiler-pause = Pause
+profiler-resume = Resume
+profiler-reset = Reset
+profiler-frame = Frame: { $milliseconds } ms ({ $fps } FPS)
+profiler-section = Section
+profiler-average = Avg/Frame
+profiler-share = Share of Frame
+profiler-graph = History
+profiler-capture-trace = Capture Trace
+profiler-capture-trace-tooltip = Record every section's timing so that hitches can be inspected in Perfetto
+profiler-save-trace = Save Last 10s
+profiler-budget = Budget (ms)
+profiler-budget-tooltip = Log a warning naming the slowest section when the mod's work in a frame takes longer than this, at most once every 10 seconds. 0 disables the warning.
+profiler-stats =
+    Since the last report (ms):
+    min { $min } | p50 { $p50 } | p95 { $p95 } | p99 { $p99 } | max { $max }
+
+## Commands
+
+command-invalid = Invalid { $command }.
+command-usage =
+    Usage:
+    { $usage }
+event-invalid = Invalid event ID: { $flag }
+event-flags-unavailable = Event flags aren't loaded yet.
+event-get = Event
+event-set = Set event
+event-set-to = to
+trace-started = Capturing a trace. Run !trace SECONDS to save it.
+trace-stopped = Stopped capturing a trace.
+trace-invalid = Invalid !trace.
+trace-usage =
+    Usage:
+    !trace start
+    !trace stop
+    !trace [SECONDS]
+trace-saved = Wrote trace to { $path }
+trace-failed = Failed to write trace: { $error }
+trace-unavailable = Tracing requires a build with the profile feature.
+loglevel-current =
+    Log file level: { $file }
+    Terminal level: { $terminal }
+loglevel-usage =
+    Usage:
+    !loglevel DIRECTIVES
+    !loglevel terminal DIRECTIVES
+    For example: !loglevel info,ds3_archipelago::item=debug,tungstenite=off
+loglevel-set = Log file level set to { $level }
+loglevel-terminal-set = Terminal log level set to { $level }
+loglevel-invalid = Invalid !loglevel: { $error }
+savedata-usage =
+    Usage:
+    !savedata export
+    !savedata import
+    !savedata import apply
+
+    Exports write your Archipelago save data to savedata.json in the mod directory. Imports show what would change before you apply them.
+savedata-failed = !savedata failed: { $error }
+savedata-not-loaded = Save data is only available while a save is loaded
+savedata-unsupported = This game doesn't support importing save data
+savedata-exported = Exported save data to { $path }. Edit it and run !savedata import to load it back in.
+savedata-read-failed = Failed to read { $path }
+savedata-invalid-json = Invalid JSON
+savedata-unchanged = The imported save data matches the current save data. Nothing to do.
+savedata-diff =
+    Importing { $path } would make these changes:
+    { $changes }
+    Run !savedata import apply to apply them.
+savedata-diff-added = { $path }: added { $entries }
+savedata-diff-removed = { $path }: removed { $entries }
+savedata-diff-changed = { $path }: { $old } -> { $new }
+savedata-entries = { $count } entries
+savedata-no-pending = Run !savedata import first to review the changes
+savedata-changed = Your save data changed since you reviewed the import. Run !savedata import again
+savedata-imported = Imported save data.
+savedata-too-many-items = items_granted is { $items_granted }, but the server has only sent { $received } items
+savedata-seed-mismatch = The imported seed { $seed } doesn't match the connected room's seed { $room_seed }
+
+## Save backups
+
+save-data-invalid =
+    Your Archipelago save data couldn't be read: { $error }
+save-data-went-backwards =
+    Your Archipelago save data looks older than your most recent backup. It has { $items_granted } items granted and { $locations } locations checked, but the backup has { $backup_items_granted } and { $backup_locations }.
+save-data-corrupt =
+    Your Archipelago save data failed its integrity check, so it was ignored. It may have been damaged or overwritten by another mod. { $error } Backups for this seed and slot are kept in { $backups }, in a folder for each character.
+save-data-checksum-mismatch = The stored checksum is { $expected }, but the data's checksum is { $actual }.
+backup-info = Most recent backup: { $path } ({ $items_granted } items granted, { $locations } locations checked)
+backup-info-any-character = Most recent backup for this seed and slot: { $path } ({ $items_granted } items granted, { $locations } locations checked). The loaded save data doesn't say which character it belongs to, so this backup may be from a different character.
+no-backup = There's no backup for this character.
+no-backup-any-character = There are no backups for this seed and slot.
+restore-backup = Restore backup
+dismiss = Dismiss
+dismiss-discards-save = Dismissing this will throw away the save data that couldn't be read. The mod will start over with empty data for this save, so the server will send you every item you've already received again. Restore a backup instead if you can.
+dismiss-anyway = Dismiss anyway
+cancel = Cancel
+restore-failed = Failed to restore backup: { $error }
+
+## Error display
+
+show-full-error = Show full error
+copy-error = Copy error
+open-log-folder = Open log folder
+retry = Retry
+save-diagnostics = Save diagnostics
+diagnostics-saved = Saved diagnostics to { $path }. Please attach this file to your bug report.
+diagnostics-failed = Failed to save diagnostics: { $error }
+exit = Exit
@@ -8,7 +8,12 @@ use log::*;
 use serde::de::DeserializeOwned;
 use ustr::Ustr;
 
-use crate::{Game, SectionProfiler, config::Config};
+use crate::event_log::{EventLog, GameEvent};
+use crate::log_filter::{self, LogFilter};
+use crate::save_data_command::PendingImport;
+use crate::{
+    DeathLinkStatus, DynSaveStore, Game, GoalRequirement, SectionProfiler, config::Config, tr,
+};
 
 /// The maximum number of log messages to store.
 const LOG_BUFFER_LIMIT: usize = 1000;
@@ -35,6 +40,10 @@ pub struct CoreBase<G: Game, S: DeserializeOwned + Send + 'static> {
     /// times they were received.
     log_buffer: VecDeque<(ap::Print, Instant)>,
 
+    /// The total number of logs that have been added to [log_buffer] over the
+    /// course of the session, including those that have since been dropped.
+    log_count: usize,
+
     /// Events we're waiting to process until the player loads a save. This is
     /// always empty unless a connection is connected and the player is on the
     /// main menu (or in the initial waiting period during a load).
@@ -53,6 +62,13 @@ pub struct CoreBase<G: Game, S: DeserializeOwned + Send + 'static> {
     /// A profiler that can be used to track how long various sections of the
     /// mod take to run.
     profiler: SectionProfiler,
+
+    /// The structured log of significant game events.
+    event_log: EventLog,
+
+    /// A save data import that the player has reviewed with `!savedata
+    /// import` but not yet applied.
+    pending_import: Option<PendingImport>,
 }
 
 impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
@@ -67,10 +83,13 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
             config,
             connection,
             log_buffer: Default::default(),
+            log_count: 0,
             event_buffer: vec![],
             load_time: None,
             error: None,
             profiler: Default::default(),
+            event_log: Default::default(),
+            pending_import: None,
         })
     }
 
@@ -94,6 +113,16 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
         &mut self.profiler
     }
 
+    /// Records [event] in the structured event log, along with the current
+    /// seed and slot.
+    pub fn record_event(&mut self, event: GameEvent) {
+        let seed = self.connection.client().map_or_else(
+            || self.config.seed().to_string(),
+            |c| c.seed_name().to_string(),
+        );
+        self.event_log.record(&seed, self.config.slot(), &event);
+    }
+
     /// Returns the current connection type.
     pub(crate) fn connection_state_type(&self) -> ap::ConnectionStateType {
         self.connection.state_type()
@@ -107,7 +136,7 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
     /// Retries the Archipelago connection with the same information.
     pub(crate) fn reconnect(&mut self) {
         if self.connection_state_type() == ap::ConnectionStateType::Disconnected {
-            self.log("Reconnecting...");
+            self.log(tr!("reconnecting"));
         }
 
         self.connection = Self::new_connection(self.game, &self.config);
@@ -117,7 +146,7 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
     /// Archipelago session.
     pub(crate) fn update_url(&mut self, url: impl AsRef<str>) -> Result<()> {
         if self.connection_state_type() == ap::ConnectionStateType::Disconnected {
-            self.log("Reconnecting...");
+            self.log(tr!("reconnecting"));
         }
 
         self.config.set_url(url);
@@ -136,17 +165,162 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
         }
     }
 
+    /// Sets this client's fatal error, which disables most in-game processing
+    /// until it's cleared. If there's already an error, the new one is only
+    /// logged, since the first is usually the root cause.
+    pub(crate) fn set_error(&mut self, err: Error) {
+        if self.error.is_some() {
+            error!("Additional error while another is pending: {err:?}");
+        } else {
+            self.error = Some(err);
+        }
+    }
+
+    /// Clears this client's fatal error, if any, so that it resumes running
+    /// the game logic. If the underlying problem hasn't been fixed, this will
+    /// likely just encounter the same error again.
+    pub(crate) fn clear_error(&mut self) {
+        self.error = None;
+    }
+
+    /// Sets the save data import that's waiting to be applied.
+    pub(crate) fn set_pending_import(&mut self, import: Option<PendingImport>) {
+        self.pending_import = import;
+    }
+
+    /// Returns the save data import that's waiting to be applied, if any.
+    pub(crate) fn pending_import(&self) -> Option<&PendingImport> {
+        self.pending_import.as_ref()
+    }
+
     /// Returns the current user config.
     pub(crate) fn config(&self) -> &Config<G> {
         &self.config
     }
 
+    /// Returns the total number of logs that have been emitted in the current
+    /// session. Unlike [logs], this includes logs that have been dropped
+    /// because the buffer filled up.
+    pub(crate) fn log_count(&self) -> usize {
+        self.log_count
+    }
+
     /// Returns the list of all logs that have been emitted in the current
     /// session.
-    pub(crate) fn logs(&self) -> impl ExactSizeIterator<Item = &(ap::Print, Instant)> {
+    pub(crate) fn logs(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &(ap::Print, Instant)> + ExactSizeIterator {
         self.log_buffer.iter()
     }
 
+    /// Handles commands that apply to every game and only need the base
+    /// struct. Returns whether a command was handled. See
+    /// [Core::handle_builtin_command].
+    pub(crate) fn handle_command(&mut self, command: &str, arg: Option<&str>) -> bool {
+        match command {
+            "!trace" => {
+                self.handle_trace_command(arg);
+                true
+            }
+            "!loglevel" => {
+                self.handle_log_level_command(arg);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles the `!loglevel` command, which shows or changes the filters for
+    /// messages written to the log file and the terminal until the game is
+    /// restarted.
+    fn handle_log_level_command(&mut self, arg: Option<&str>) {
+        let arg = arg.map(str::trim).filter(|a| !a.is_empty());
+        let (terminal, directives) = match arg {
+            Some(arg) => match arg.strip_prefix("terminal") {
+                Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => {
+                    (true, Some(rest.trim()).filter(|r| !r.is_empty()))
+                }
+                _ => (false, Some(arg)),
+            },
+            None => (false, None),
+        };
+
+        let Some(directives) = directives else {
+            self.log(tr!(
+                "loglevel-current",
+                file = log_filter::file_filter(),
+                terminal = log_filter::terminal_filter(),
+            ));
+            self.log(tr!("loglevel-usage"));
+            return;
+        };
+
+        match directives.parse::<LogFilter>() {
+            Ok(filter) if terminal => {
+                log_filter::set_terminal_filter(filter);
+                let level = log_filter::terminal_filter();
+                info!("Terminal log level changed to {level}");
+                self.log(tr!("loglevel-terminal-set", level = level));
+            }
+            Ok(filter) => {
+                log_filter::set_file_filter(filter);
+                let level = log_filter::file_filter();
+                info!("Log level changed to {level}");
+                self.log(tr!("loglevel-set", level = level));
+            }
+            Err(err) => self.log(ap::RichText::Color {
+                text: tr!("loglevel-invalid", error = err),
+                color: ap::TextColor::Red,
+            }),
+        }
+    }
+
+    /// Handles the `!trace` command, which controls capturing a Chrome Trace
+    /// Event file from the [SectionProfiler].
+    #[cfg(feature = "profile")]
+    fn handle_trace_command(&mut self, arg: Option<&str>) {
+        let message = match arg.map(str::trim).filter(|a| !a.is_empty()) {
+            Some("start") => {
+                self.profiler.set_capturing(true);
+                tr!("trace-started")
+            }
+            Some("stop") => {
+                self.profiler.set_capturing(false);
+                tr!("trace-stopped")
+            }
+            arg => {
+                let Some(seconds) = arg.map_or(Some(10), |a| a.parse::<u64>().ok()) else {
+                    self.log(ap::RichText::Color {
+                        text: tr!("trace-invalid"),
+                        color: ap::TextColor::Red,
+                    });
+                    self.log(tr!("trace-usage"));
+                    return;
+                };
+
+                match self.profiler.write_trace(Duration::from_secs(seconds)) {
+                    Ok(path) => tr!("trace-saved", path = path.to_string_lossy()),
+                    Err(err) => {
+                        self.log(ap::RichText::Color {
+                            text: tr!("trace-failed", error = err),
+                            color: ap::TextColor::Red,
+                        });
+                        return;
+                    }
+                }
+            }
+        };
+        self.log(message);
+    }
+
+    #[cfg(not(feature = "profile"))]
+    fn handle_trace_command(&mut self, _arg: Option<&str>) {
+        self.log(ap::RichText::Color {
+            text: tr!("trace-unavailable"),
+            color: ap::TextColor::Red,
+        });
+    }
+
     /// Updates the Archipelago connection, adds any events that need processing
     /// to [event_buffer].
     ///
@@ -175,17 +349,15 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
                         {
                             vec![
                                 ap::RichText::Color {
-                                    text: "Connection refused. ".into(),
+                                    text: format!("{} ", tr!("connection-refused")).into(),
                                     color: ap::TextColor::Red,
                                 },
-                                "Make sure the server session is running and the URL is \
-                                 up-to-date."
-                                    .into(),
+                                tr!("connection-refused-hint").into(),
                             ]
                         } else if state == ap::ConnectionStateType::Connected {
                             vec![
                                 ap::RichText::Color {
-                                    text: "Connection failed: ".into(),
+                                    text: format!("{} ", tr!("connection-failed")).into(),
                                     color: ap::TextColor::Red,
                                 },
                                 err.to_string().into(),
@@ -193,7 +365,7 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
                         } else {
                             vec![
                                 ap::RichText::Color {
-                                    text: "Disconnected: ".into(),
+                                    text: format!("{} ", tr!("disconnected")).into(),
                                     color: ap::TextColor::Red,
                                 },
                                 err.to_string().into(),
@@ -205,10 +377,7 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
                 Error(err) => self.log(err.to_string()),
                 Print(print) => {
                     info!("[APS] {print}");
-                    if self.log_buffer.len() >= LOG_BUFFER_LIMIT {
-                        self.log_buffer.pop_front();
-                    }
-                    self.log_buffer.push_back((print, Instant::now()));
+                    self.push_log(print);
                 }
                 _ => {}
             }
@@ -227,12 +396,11 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
         if let Some(client_version) = self.config().client_version()
             && client_version != expected_version
         {
-            bail!(
-                "Your apconfig.json was generated using static randomizer v{}, but this client is \
-                 v{}. Re-run the static randomizer with the current version.",
-                client_version,
-                expected_version,
-            );
+            bail!(tr!(
+                "version-conflict",
+                config_version = client_version,
+                client_version = expected_version,
+            ));
         } else {
             Ok(())
         }
@@ -243,12 +411,18 @@ impl<G: Game, S: DeserializeOwned + Send + 'static> CoreBase<G, S> {
     fn log(&mut self, message: impl Into<ap::Print>) {
         let print = message.into();
         info!("[APC] {print}");
+        self.push_log(print);
+    }
+
+    /// Adds [print] to the log buffer, dropping the oldest log if it's full.
+    fn push_log(&mut self, print: ap::Print) {
         // Consider making this a circular buffer if it ends up eating too much
         // memory over time.
         if self.log_buffer.len() >= LOG_BUFFER_LIMIT {
             self.log_buffer.pop_front();
         }
         self.log_buffer.push_back((print, Instant::now()));
+        self.log_count += 1;
     }
 }
 
@@ -284,6 +458,47 @@ pub trait Core: Send + Sized {
         false
     }
 
+    /// Implementors may override this to return the current state of death
+    /// link, which is displayed in the overlay's menu bar.
+    ///
+    /// By default, this returns None, which hides the death link status.
+    fn death_link_status(&self) -> Option<DeathLinkStatus> {
+        None
+    }
+
+    /// Implementors may override this to return the store for the player's
+    /// save data, which lets the player export and import it with the
+    /// `!savedata` command.
+    ///
+    /// By default, this returns None.
+    fn save_store(&self) -> Option<&'static dyn DynSaveStore> {
+        None
+    }
+
+    /// Returns the player's save data as JSON, or None if it's not available.
+    /// This is included in diagnostic bundles.
+    fn save_data_json(&self) -> Option<json::Value> {
+        self.save_store()?.to_json()
+    }
+
+    /// Implementors may override this to return a human-readable summary of
+    /// the slot data, which is included in diagnostic bundles.
+    ///
+    /// By default, this returns None.
+    fn slot_data_summary(&self) -> Option<String> {
+        None
+    }
+
+    /// Implementors may override this to return each requirement for the
+    /// player's goal and whether it's been met, which is displayed in the
+    /// overlay's menu bar. This should return None if progress can't currently
+    /// be determined.
+    ///
+    /// By default, this returns None, which hides the goal progress.
+    fn goal_progress(&self) -> Option<Vec<GoalRequirement>> {
+        None
+    }
+
     /// Returns a reference to the Archipelago client, if it's connected.
     fn client(&self) -> Option<&ap::Client<Self::SlotData>> {
         self.base().connection.client()
@@ -305,18 +520,58 @@ pub trait Core: Send + Sized {
         self.base_mut().log(message);
     }
 
+    /// Records [event] in the structured event log.
+    fn record_event(&mut self, event: GameEvent) {
+        self.base_mut().record_event(event);
+    }
+
     /// Consumes and returns all the as-yet-unprocessed events from the player's
     /// save.
     fn take_events(&mut self) -> Vec<ap::Event> {
         mem::take(&mut self.base_mut().event_buffer)
     }
 
+    /// Handles commands that apply to every game. Returns whether a command
+    /// was handled. This is only called for commands that
+    /// [Self::handle_command] didn't handle. Implementations should not
+    /// override this.
+    fn handle_builtin_command(&mut self, command: &str, arg: Option<&str>) -> bool {
+        match command {
+            "!diagnostics" => {
+                match crate::diagnostics::write_bundle(Some(self), None) {
+                    Ok(path) => self.log(tr!("diagnostics-saved", path = path.to_string_lossy())),
+                    Err(err) => self.log(ap::RichText::Color {
+                        text: tr!("diagnostics-failed", error = err),
+                        color: ap::TextColor::Red,
+                    }),
+                }
+                true
+            }
+            "!savedata" => {
+                crate::save_data_command::handle(self, arg);
+                true
+            }
+            _ => self.base_mut().handle_command(command, arg),
+        }
+    }
+
     /// Runs the core logic of the mod. This may set [error], which should be
     /// surfaced to the user. Implementations should not override this; they
     /// should override [Self::update_live] instead.
     fn update(&mut self, is_main_menu: bool) {
         self.base_mut().update_always();
 
+        if let Some(store) = self.save_store() {
+            let config = &self.base().config;
+            store.set_session(config.seed(), config.slot());
+
+            // Don't grant items against save data that can't be used or that
+            // the player may be about to replace with a backup.
+            if store.is_paused() {
+                return;
+            }
+        }
+
         if self.base().connection.client().is_none() || self.base().error.is_some() {
             return;
         }
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+/// The conditions under which a player's deaths are sent out as death links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathLinkMode {
+    /// Death link is disabled.
+    Off,
+
+    /// Death link triggers on any death.
+    AnyDeath,
+
+    /// Death link only triggers when the player dies without recovering their
+    /// lost souls or experience.
+    LostSouls,
+}
+
+/// Whether a [DeathLinkEvent] was sent by this player or received from
+/// another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeathLinkDirection {
+    /// This player died and sent a death link to their teammates.
+    Sent,
+
+    /// Another player died and this player was killed as a result.
+    Received,
+}
+
+/// A single death link that was sent or received during this session.
+#[derive(Debug, Clone)]
+pub struct DeathLinkEvent {
+    /// Whether this death link was sent or received.
+    pub direction: DeathLinkDirection,
+
+    /// The name of the player who died.
+    pub source: String,
+
+    /// The cause of death reported by [source], if any.
+    pub cause: Option<String>,
+
+    /// When the death link was sent or received.
+    pub time: Instant,
+}
+
+/// A snapshot of a game's death link state, for display in the overlay.
+#[derive(Debug, Clone)]
+pub struct DeathLinkStatus {
+    /// The player's death link setting.
+    pub mode: DeathLinkMode,
+
+    /// The number of deaths it takes to send a death link.
+    pub amnesty: u8,
+
+    /// The number of deaths the player has experienced since they last sent a
+    /// death link.
+    pub deaths: u8,
+
+    /// The time remaining in the grace period after the last death link
+    /// during which no more will be sent or received, or None if the grace
+    /// period isn't active.
+    pub grace_period: Option<Duration>,
+
+    /// The most recent death link sent or received this session, if any.
+    pub last_event: Option<DeathLinkEvent>,
+}
+
+impl DeathLinkStatus {
+    /// Returns the number of deaths the player can experience before sending a
+    /// death link.
+    pub fn remaining_amnesty(&self) -> u8 {
+        self.amnesty.saturating_sub(self.deaths)
+    }
+}
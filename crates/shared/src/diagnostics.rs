@@ -0,0 +1,130 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+use chrono::prelude::*;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{Core, Game, l10n, log_file, utils};
+
+/// The number of most recent log files to include in a diagnostic bundle.
+const LOG_FILES: usize = 3;
+
+/// Writes a zip file to the mod directory containing everything we typically
+/// need to debug a player's problem, and returns its path.
+///
+/// If `core` is None (for example because it failed to initialize), the bundle
+/// omits the information that comes from it. If `error` is set, it's included
+/// in full.
+pub(crate) fn write_bundle<C: Core>(
+    core: Option<&mut C>,
+    error: Option<&Error>,
+) -> Result<PathBuf> {
+    let dir = utils::mod_directory()?;
+    let path = dir.join(
+        Local::now()
+            .format("diagnostics-%Y-%m-%d-%H-%M-%S.zip")
+            .to_string(),
+    );
+
+    let mut zip = ZipWriter::new(fs::File::create(&path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for log in recent_logs(&dir.join("log"))? {
+        let name = format!("log/{}", log.file_name().unwrap().to_string_lossy());
+        zip.start_file(name, options)?;
+        zip.write_all(&fs::read(&log)?)?;
+    }
+
+    zip.start_file("apconfig.json", options)?;
+    zip.write_all(redacted_config(dir).as_bytes())?;
+
+    zip.start_file("versions.txt", options)?;
+    zip.write_all(versions::<C::Game>().as_bytes())?;
+
+    if let Some(error) = error {
+        zip.start_file("error.txt", options)?;
+        zip.write_all(format!("{error:?}\n").as_bytes())?;
+    }
+
+    if let Some(core) = core {
+        zip.start_file("save_data.json", options)?;
+        zip.write_all(
+            match core.save_data_json() {
+                Some(save) => json::to_string_pretty(&save)?,
+                None => "null".into(),
+            }
+            .as_bytes(),
+        )?;
+
+        zip.start_file("slot_data.txt", options)?;
+        zip.write_all(
+            core.slot_data_summary()
+                .unwrap_or_else(|| "Not connected.\n".into())
+                .as_bytes(),
+        )?;
+
+        zip.start_file("profiler.txt", options)?;
+        zip.write_all(core.base_mut().profiler().summary().as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(path)
+}
+
+/// Returns the paths of the [LOG_FILES] most recent log files in `dir`.
+fn recent_logs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut logs = log_file::log_files(dir)?;
+    logs.sort_by_cached_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+    Ok(logs.into_iter().rev().take(LOG_FILES).collect())
+}
+
+/// Returns the contents of the config file in `dir` with the password
+/// stripped. This reads the file directly rather than going through
+/// [crate::config::Config] so that it works even if the config is invalid.
+fn redacted_config(dir: &Path) -> String {
+    let text = match fs::read_to_string(dir.join("apconfig.json")) {
+        Ok(text) => text,
+        Err(err) => return format!("Failed to read apconfig.json: {err}\n"),
+    };
+
+    match json::from_str::<json::Value>(&text) {
+        Ok(mut config) => {
+            if let Some(password) = config.get_mut("password")
+                && !password.is_null()
+            {
+                *password = "<redacted>".into();
+            }
+            json::to_string_pretty(&config).unwrap_or_default()
+        }
+        // Don't include the raw text, since we can't be sure we've removed the
+        // password from it.
+        Err(err) => format!("Failed to parse apconfig.json: {err}\n"),
+    }
+}
+
+/// Returns a human-readable list of the versions of the client, the game, and
+/// the player's environment.
+fn versions<G: Game>() -> String {
+    let mut versions = String::new();
+    let _ = writeln!(versions, "Game: {}", G::TYPE.short_name());
+    let _ = writeln!(versions, "Client version: {}", G::CLIENT_VERSION);
+    let _ = writeln!(
+        versions,
+        "Game version: {}",
+        utils::game_version().unwrap_or_else(|err| format!("unknown ({err})"))
+    );
+    let _ = writeln!(versions, "Locale: {}", l10n::locale().name());
+    let _ = writeln!(
+        versions,
+        "Profiling: {}",
+        if cfg!(feature = "profile") {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    versions
+}
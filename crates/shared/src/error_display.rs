@@ -1,10 +1,46 @@
+use std::process::Command;
 use std::sync::{Arc, Mutex};
 
 use anyhow::{Error, Result};
+use archipelago_rs as ap;
 use hudhook::{ImguiRenderLoop, RenderContext};
 use imgui::*;
+use log::*;
 
-use crate::{Core, Game, InputBlocker, InputFlags, overlay::Overlay, utils::PopupModalExt};
+use crate::overlay::{Fonts, Overlay, OverlaySettings};
+use crate::{
+    Core, Game, InputBlocker, InputFlags, diagnostics, l10n, tr, utils, utils::PopupModalExt,
+};
+
+/// How to recover from the error that an [ErrorDisplay] is showing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Retry {
+    /// Try to create the core again. Used when [Core::new] failed, for example
+    /// because the config couldn't be loaded.
+    CreateCore,
+
+    /// Clear the core's error and let it keep running. Used when the core
+    /// encountered an error while updating that may not happen again, such as
+    /// losing the connection to the server. See [is_retryable].
+    ClearCoreError,
+}
+
+/// Returns whether the core may be able to keep running after `err` if it's
+/// cleared. This is true of errors talking to the Archipelago server, which
+/// are usually caused by a dropped connection. Other errors, such as seed
+/// conflicts and caught panics, will just happen again (or leave the mod in an
+/// unknown state), so the player has to fix the problem and restart instead.
+fn is_retryable(err: &Error) -> bool {
+    err.chain().any(|cause| cause.is::<ap::Error>())
+}
+
+/// Opens the directory containing the mod's log files in Explorer.
+fn open_log_folder() -> Result<()> {
+    Command::new("explorer")
+        .arg(utils::mod_directory()?.join("log"))
+        .spawn()?;
+    Ok(())
+}
 
 /// A wrapper around the rest of the mod's UI that doesn't expect any state to
 /// exist. This allows the full [Overlay] to assume that its [Core] exists while
@@ -26,12 +62,25 @@ pub(crate) struct ErrorDisplay<G: Game> {
     /// fatal errors are detected later.
     error: Option<Error>,
 
+    /// How to recover from [error], or None if it can't be recovered from
+    /// without restarting the game.
+    retry: Option<Retry>,
+
+    /// Fonts that were loaded before the overlay existed. If the overlay is
+    /// created later by retrying, these are handed over to it on the next
+    /// frame.
+    fonts: Option<Fonts>,
+
     /// Whether to display the full error information or just the summary.
     show_full_error: bool,
+
+    /// The result of the last attempt to save a diagnostic bundle, if any.
+    diagnostics_message: Option<String>,
 }
 
 impl<G: Game> ErrorDisplay<G> {
-    /// Creates a new [ErrorDisplay].
+    /// Creates a new [ErrorDisplay]. If `core` is an error, the player can
+    /// retry creating it.
     pub fn new(core: Result<Arc<Mutex<G::Core>>>, input_blocker: G::InputBlocker) -> Self {
         match core {
             Ok(core) => Self {
@@ -39,15 +88,62 @@ impl<G: Game> ErrorDisplay<G> {
                 overlay: Some(Overlay::new()),
                 core: Some(core),
                 error: None,
+                retry: None,
+                fonts: None,
                 show_full_error: false,
+                diagnostics_message: None,
             },
-            Err(error) => Self {
-                input_blocker,
-                overlay: None,
-                core: None,
-                error: Some(error),
-                show_full_error: false,
+            Err(error) => {
+                let mut display = Self::fatal(error, input_blocker);
+                display.retry = Some(Retry::CreateCore);
+                display
+            }
+        }
+    }
+
+    /// Creates a new [ErrorDisplay] for an error that can't be recovered from
+    /// without restarting the game.
+    pub fn fatal(error: Error, input_blocker: G::InputBlocker) -> Self {
+        // The overlay normally applies the player's locale, so do so here
+        // instead since it won't exist.
+        l10n::set_locale(OverlaySettings::load().locale);
+
+        Self {
+            input_blocker,
+            overlay: None,
+            core: None,
+            error: Some(error),
+            retry: None,
+            fonts: None,
+            show_full_error: false,
+            diagnostics_message: None,
+        }
+    }
+
+    /// Attempts to recover from the current error according to [retry].
+    fn retry(&mut self) {
+        match self.retry {
+            Some(Retry::CreateCore) => match crate::create_core::<G>() {
+                Ok(core) => {
+                    info!("Core created after retrying.");
+                    self.core = Some(core);
+                    self.overlay = Some(Overlay::new());
+                    self.error = None;
+                    self.retry = None;
+                }
+                Err(err) => {
+                    error!("Failed to create core after retrying: {err:?}");
+                    self.error = Some(err);
+                }
             },
+            Some(Retry::ClearCoreError) => {
+                if let Some(core) = &self.core {
+                    crate::lock_core(core).base_mut().clear_error();
+                }
+                self.error = None;
+                self.retry = None;
+            }
+            None => {}
         }
     }
 
@@ -70,6 +166,8 @@ impl<G: Game> ErrorDisplay<G> {
             );
         }
 
+        let mut retry = false;
+        let mut save_diagnostics = false;
         ui.open_popup("#fatal-error");
         ui.modal_popup_config("#fatal-error")
             .title_bar(false)
@@ -80,7 +178,7 @@ impl<G: Game> ErrorDisplay<G> {
                 Condition::Always,
             )
             .build(|| {
-                ui.checkbox("Show full error", &mut self.show_full_error);
+                ui.checkbox(tr!("show-full-error"), &mut self.show_full_error);
                 ui.text_wrapped(if self.show_full_error {
                     format!("{:?}", error)
                 } else {
@@ -88,10 +186,50 @@ impl<G: Game> ErrorDisplay<G> {
                 });
 
                 ui.separator();
-                if ui.button("Exit") {
+                if ui.button(tr!("copy-error")) {
+                    ui.set_clipboard_text(format!("{:?}", error));
+                }
+                ui.same_line();
+                if ui.button(tr!("open-log-folder"))
+                    && let Err(err) = open_log_folder()
+                {
+                    error!("Failed to open log folder: {err:?}");
+                }
+                ui.same_line();
+                save_diagnostics = ui.button(tr!("save-diagnostics"));
+                if self.retry.is_some() {
+                    ui.same_line();
+                    if ui.button(tr!("retry")) {
+                        ui.close_current_popup();
+                        retry = true;
+                    }
+                }
+                ui.same_line();
+                if ui.button(tr!("exit")) {
                     std::process::exit(1);
                 }
+
+                if let Some(message) = &self.diagnostics_message {
+                    ui.text_wrapped(message);
+                }
             });
+
+        if save_diagnostics {
+            let mut core = self.core.as_deref().map(crate::lock_core);
+            self.diagnostics_message = Some(
+                match diagnostics::write_bundle(core.as_deref_mut(), self.error.as_ref()) {
+                    Ok(path) => tr!("diagnostics-saved", path = path.to_string_lossy()),
+                    Err(err) => {
+                        error!("Failed to write diagnostics: {err:?}");
+                        tr!("diagnostics-failed", error = err)
+                    }
+                },
+            );
+        }
+
+        if retry {
+            self.retry();
+        }
     }
 }
 
@@ -105,21 +243,29 @@ impl<G: Game> ImguiRenderLoop for ErrorDisplay<G> {
         if io.want_capture_keyboard {
             flag |= InputFlags::Keyboard;
         }
-        if io.want_capture_mouse && io.want_capture_keyboard {
+        if (io.want_capture_mouse && io.want_capture_keyboard)
+            || self.overlay.as_ref().is_some_and(|o| o.wants_gamepad())
+        {
             // Only block pad input if both the mouse and keyboard are blocked
-            // (for example if a modal dialog is up).
+            // (for example if a modal dialog is up) or the player is navigating
+            // the overlay with their controller.
             flag |= InputFlags::GamePad;
         }
         self.input_blocker.block_only(flag);
 
         if let Some(core) = &mut self.core {
-            let mut core = core.lock().unwrap();
+            let mut core = crate::lock_core(core);
             if let Some(overlay) = &mut self.overlay {
                 overlay.render(ui, &mut core);
             }
 
             if self.error.is_none() {
                 self.error = core.base_mut().take_error();
+                self.retry = self
+                    .error
+                    .as_ref()
+                    .filter(|err| is_retryable(err))
+                    .map(|_| Retry::ClearCoreError);
             }
         }
 
@@ -128,6 +274,15 @@ impl<G: Game> ImguiRenderLoop for ErrorDisplay<G> {
 
     fn initialize<'a>(&'a mut self, ctx: &mut Context, _render_context: &'a mut dyn RenderContext) {
         ctx.set_clipboard_backend(crate::clipboard::WindowsClipboardBackend {});
+
+        // Load the fonts even if the overlay doesn't exist so the error is
+        // rendered the same way it would be if the overlay existed.
+        let fonts = Fonts::load(ctx);
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.initialize(ctx, fonts);
+        } else {
+            self.fonts = Some(fonts);
+        }
     }
 
     fn before_render<'a>(
@@ -135,6 +290,14 @@ impl<G: Game> ImguiRenderLoop for ErrorDisplay<G> {
         ctx: &mut Context,
         render_context: &'a mut dyn RenderContext,
     ) {
+        // If the overlay was created by retrying, it hasn't been initialized
+        // yet.
+        if let Some(overlay) = self.overlay.as_mut()
+            && let Some(fonts) = self.fonts.take()
+        {
+            overlay.initialize(ctx, fonts);
+        }
+
         if let Some(overlay) = self.overlay.as_mut() {
             overlay.before_render(ctx, render_context);
         } else {
@@ -0,0 +1,149 @@
+use std::fs::{self, File};
+use std::io::{LineWriter, Write};
+
+use anyhow::Result;
+use chrono::prelude::*;
+use log::*;
+use serde::Serialize;
+
+use crate::log_file::EVENTS_PREFIX;
+use crate::utils;
+
+/// A game event that's significant enough to record in the structured event
+/// log. Unlike the free-form text log, each of these is written as a single
+/// JSON object so that tools can consume them without parsing prose.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// The mod granted the player an item they received from the server.
+    ItemGranted {
+        /// The index of the item in the list of items the player has received
+        /// from the server.
+        index: usize,
+
+        /// The Archipelago ID of the item.
+        item_id: i64,
+
+        /// The name of the item.
+        item_name: String,
+
+        /// The game's own ID for the item, in the game's debug format.
+        game_item_id: String,
+
+        /// The number of copies of the item that were granted.
+        quantity: u32,
+
+        /// The Archipelago ID of the location the item was found in.
+        location_id: i64,
+
+        /// The name of the player who found the item.
+        sender: String,
+    },
+
+    /// The player checked a location in their own world.
+    LocationChecked {
+        /// The Archipelago ID of the location.
+        location_id: i64,
+    },
+
+    /// This player died and sent a death link to their teammates.
+    DeathLinkSent,
+
+    /// This player died but the death link was absorbed by their amnesty.
+    DeathLinkAmnesty {
+        /// The number of deaths remaining before a death link is sent.
+        remaining: u8,
+    },
+
+    /// Another player died and this player was killed as a result.
+    DeathLinkReceived {
+        /// The name of the player who sent the death link.
+        source: String,
+
+        /// The reason the player died, if they gave one.
+        cause: Option<String>,
+    },
+
+    /// The player completed their goal.
+    GoalCompleted,
+}
+
+/// A [GameEvent] along with the context needed to interpret it on its own.
+#[derive(Serialize)]
+struct EventRecord<'a> {
+    /// The time at which the event happened, in RFC 3339 format.
+    timestamp: String,
+
+    /// The seed of the multiworld the player was connected to.
+    seed: &'a str,
+
+    /// The name of the player's slot.
+    slot: &'a str,
+
+    /// The event itself.
+    #[serde(flatten)]
+    event: &'a GameEvent,
+}
+
+/// A JSON-lines file in the mod's log directory to which [GameEvent]s are
+/// written, one per line. A new file is started for each session, but only
+/// once the first event is recorded. Old files are compressed and pruned along
+/// with the text logs by [crate::log_file::LogFile].
+#[derive(Default)]
+pub(crate) struct EventLog {
+    /// The file being written, or None if no events have been recorded yet.
+    file: Option<LineWriter<File>>,
+
+    /// Whether we've failed to write to the file. Once this is set, no more
+    /// events are recorded so that we don't spam the log with warnings.
+    failed: bool,
+}
+
+impl EventLog {
+    /// Writes [event] to the event log.
+    pub fn record(&mut self, seed: &str, slot: &str, event: &GameEvent) {
+        if self.failed {
+            return;
+        }
+
+        if let Err(err) = self.write(seed, slot, event) {
+            warn!("Failed to write to event log, disabling it: {err:?}");
+            self.failed = true;
+        }
+    }
+
+    /// Serializes [event] and writes it to the file, opening it if necessary.
+    fn write(&mut self, seed: &str, slot: &str, event: &GameEvent) -> Result<()> {
+        let file = match self.file {
+            Some(ref mut file) => file,
+            None => self.file.insert(open_file()?),
+        };
+
+        let record = EventRecord {
+            timestamp: Local::now().to_rfc3339(),
+            seed,
+            slot,
+            event,
+        };
+        json::to_writer(&mut *file, &record)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Creates a new event log file for this session.
+fn open_file() -> Result<LineWriter<File>> {
+    let dir = utils::mod_directory()?.join("log");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "{EVENTS_PREFIX}{}.jsonl",
+        Local::now().format("%Y-%m-%d-%H-%M-%S")
+    ));
+    info!("Recording game events to {}", path.to_string_lossy());
+    Ok(LineWriter::new(
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?,
+    ))
+}
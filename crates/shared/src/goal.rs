@@ -0,0 +1,10 @@
+/// A single condition the player must meet to achieve their goal, such as
+/// defeating a particular boss.
+#[derive(Debug, Clone)]
+pub struct GoalRequirement {
+    /// The human-readable name of this requirement.
+    pub name: String,
+
+    /// Whether the player has met this requirement.
+    pub complete: bool,
+}
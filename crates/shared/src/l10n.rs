@@ -0,0 +1,259 @@
+//! A minimal message catalog for user-facing text.
+//!
+//! Catalogs live in `shared/locales` and use a subset of the
+//! [Fluent](https://projectfluent.org/) syntax: each message is a `key = value`
+//! pair, values may continue onto following indented lines, and `{ $name }`
+//! placeables are replaced with the arguments passed to [crate::tr]. Messages
+//! missing from a catalog fall back to English, so translations don't have to
+//! be complete to be useful.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+/// The locale that's currently used to translate messages.
+static CURRENT: RwLock<Locale> = RwLock::new(Locale::English);
+
+/// The English catalog, which is the fallback for all other locales.
+static ENGLISH: LazyLock<HashMap<&'static str, String>> =
+    LazyLock::new(|| parse(include_str!("../locales/en.ftl")));
+
+/// The German catalog.
+static GERMAN: LazyLock<HashMap<&'static str, String>> =
+    LazyLock::new(|| parse(include_str!("../locales/de.ftl")));
+
+/// A language the mod's text can be displayed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// English, which every message is guaranteed to have a translation for.
+    #[default]
+    #[serde(rename = "en")]
+    English,
+
+    /// German.
+    #[serde(rename = "de")]
+    German,
+}
+
+impl Locale {
+    /// All supported locales, in the order they're displayed in the settings.
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::German];
+
+    /// Returns the name of this locale in its own language.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::German => "Deutsch",
+        }
+    }
+
+    /// Returns the message catalog for this locale.
+    fn catalog(&self) -> &'static HashMap<&'static str, String> {
+        match self {
+            Locale::English => &ENGLISH,
+            Locale::German => &GERMAN,
+        }
+    }
+}
+
+/// Returns the locale that's currently used to translate messages.
+pub fn locale() -> Locale {
+    *CURRENT.read().unwrap()
+}
+
+/// Sets the locale to use to translate messages from now on.
+pub fn set_locale(locale: Locale) {
+    *CURRENT.write().unwrap() = locale;
+}
+
+/// Returns the message for [key] in the current locale with [args]
+/// substituted in. Usually called via [crate::tr].
+pub fn translate(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate_in(locale().catalog(), key, args)
+}
+
+/// Returns the message for [key] in [catalog], or in English if [catalog]
+/// doesn't have it, with [args] substituted in.
+fn translate_in(
+    catalog: &HashMap<&'static str, String>,
+    key: &str,
+    args: &[(&str, &dyn Display)],
+) -> String {
+    let Some(message) = catalog.get(key).or_else(|| ENGLISH.get(key)) else {
+        warn!("Missing translation for {key}");
+        return key.to_string();
+    };
+
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message.as_str();
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|i| start + i) else {
+            break;
+        };
+
+        let placeable = rest[start + 1..end].trim();
+        if let Some(name) = placeable.strip_prefix('$') {
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => result.push_str(&value.to_string()),
+                None => {
+                    warn!("Missing argument {name} for {key}");
+                    result.push_str(placeable);
+                }
+            }
+        } else if let Some(literal) = placeable
+            .strip_prefix('"')
+            .and_then(|p| p.strip_suffix('"'))
+        {
+            result.push_str(literal);
+        } else {
+            result.push_str(placeable);
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parses a catalog in our subset of the Fluent syntax.
+fn parse(source: &'static str) -> HashMap<&'static str, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+    for line in source.lines() {
+        if line.starts_with(' ')
+            && let Some((_, lines)) = current.as_mut()
+        {
+            lines.push(line.trim());
+            continue;
+        } else if line.trim().is_empty() {
+            // Blank lines are only meaningful within a multiline value, which
+            // is determined by whether there are more indented lines after.
+            if let Some((_, lines)) = current.as_mut() {
+                lines.push("");
+            }
+            continue;
+        }
+
+        if let Some((key, lines)) = current.take() {
+            messages.insert(key, join_lines(lines));
+        }
+
+        if line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once('=') {
+            Some((key, value)) => current = Some((key.trim(), vec![value.trim()])),
+            None => warn!("Invalid catalog line: {line}"),
+        }
+    }
+
+    if let Some((key, lines)) = current {
+        messages.insert(key, join_lines(lines));
+    }
+    messages
+}
+
+/// Joins the lines of a multiline Fluent value, dropping the leading line if
+/// it's empty (as when the value starts on the line after the key) along with
+/// any trailing blank lines.
+fn join_lines(mut lines: Vec<&str>) -> String {
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    if lines.first() == Some(&"") {
+        lines.remove(0);
+    }
+    lines.join("\n")
+}
+
+/// Translates a message from the current locale's catalog. Arguments are
+/// passed as `name = value` pairs, where each value implements [Display].
+///
+/// ```ignore
+/// tr!("connection-failed", error = err)
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::l10n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::l10n::translate(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_line_messages() {
+        let catalog = parse("# A comment\n\nconnect = Connect\nretry =  Retry  \n");
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog["connect"], "Connect");
+        assert_eq!(catalog["retry"], "Retry");
+    }
+
+    #[test]
+    fn joins_continuation_lines() {
+        let catalog =
+            parse("usage =\n    Usage:\n    !trace start\n\n    Details.\n\nnext = Next\n");
+        assert_eq!(catalog["usage"], "Usage:\n!trace start\n\nDetails.");
+        assert_eq!(catalog["next"], "Next");
+    }
+
+    #[test]
+    fn continues_inline_values() {
+        let catalog = parse("stats = Since the last report:\n    min { $min }\n");
+        assert_eq!(catalog["stats"], "Since the last report:\nmin { $min }");
+    }
+
+    #[test]
+    fn substitutes_placeholders() {
+        let catalog = parse("title = Client { $version } [{ $status }]{ \"!\" }\n");
+        assert_eq!(
+            translate_in(
+                &catalog,
+                "title",
+                &[("version", &"1.0"), ("status", &"Connected")],
+            ),
+            "Client 1.0 [Connected]!"
+        );
+    }
+
+    #[test]
+    fn keeps_missing_placeholders() {
+        let catalog = parse("saved = Saved to { $path }\n");
+        assert_eq!(translate_in(&catalog, "saved", &[]), "Saved to $path");
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        let catalog = parse("connect = Verbinden\n");
+        assert_eq!(translate_in(&catalog, "connect", &[]), "Verbinden");
+        assert_eq!(translate_in(&catalog, "reconnect", &[]), "Reconnect");
+        assert_eq!(
+            translate_in(&catalog, "trace-saved", &[("path", &"trace.json")]),
+            "Wrote trace to trace.json"
+        );
+    }
+
+    #[test]
+    fn returns_key_for_unknown_messages() {
+        assert_eq!(translate_in(&ENGLISH, "no-such-key", &[]), "no-such-key");
+    }
+
+    #[test]
+    fn translations_only_use_english_keys() {
+        for key in GERMAN.keys() {
+            assert!(ENGLISH.contains_key(key), "{key} is missing from en.ftl");
+        }
+    }
+}
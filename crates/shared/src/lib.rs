@@ -1,9 +1,8 @@
-use std::sync::{Arc, Mutex};
-use std::{fs, panic, path::Path};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::{env, panic, path::Path};
 
 use anyhow::Result;
 use backtrace::Backtrace;
-use chrono::prelude::*;
 use hudhook::Hudhook;
 use log::*;
 use simplelog::{ColorChoice, CombinedLogger, SharedLogger, TermLogger, TerminalMode, WriteLogger};
@@ -13,36 +12,53 @@ use windows::core::*;
 mod clipboard;
 mod config;
 mod core;
+mod death_link;
+mod diagnostics;
 mod error_display;
+mod event_log;
 mod game;
+mod goal;
 mod input_blocker;
+pub mod l10n;
+mod log_file;
+mod log_filter;
 mod overlay;
+mod panic_guard;
+mod save_backup;
+mod save_data_command;
+pub mod save_format;
+mod save_store;
 mod section_profiler;
 pub mod utils;
 
 pub use core::*;
+pub use death_link::*;
 use error_display::*;
+pub use event_log::GameEvent;
 pub use game::*;
+pub use goal::*;
 pub use input_blocker::*;
+use log_file::{LogFile, LogSettings};
+use log_filter::{Filtered, LogFilter, Sink};
+pub use panic_guard::{catch_hook_panic, report_hook_error};
+pub use save_store::*;
 pub(crate) use section_profiler::*;
 
 /// Handle panics by both logging and popping up a message box, which is the
 /// most reliable way to make something visible to the end user.
 pub fn handle_panics<G: Game>() {
     panic::set_hook(Box::new(|panic_info| {
-        let mut message = String::new();
-        if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
-            message.push_str(&format!("Rust panic: {s}"));
-        } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
-            message.push_str(&format!("Rust panic: {s}"));
-        } else {
-            message.push_str(&format!("Rust panic: {:?}", panic_info.payload()));
-        }
-
-        message.push_str(&format!("\n{:?}", Backtrace::new()));
-
+        let message = format!(
+            "Rust panic: {}\n{:?}",
+            panic_guard::panic_message(panic_info.payload()),
+            Backtrace::new()
+        );
         error!("{}", message);
-        message_box::<G>(message);
+
+        // Panics that are caught are displayed in the overlay instead.
+        if !panic_guard::is_catching() {
+            message_box::<G>(message);
+        }
     }));
 }
 
@@ -75,31 +91,60 @@ pub fn start_logger() {
 
 /// Starts a logger for the given directory.
 fn start_logger_for_dir(dir: impl AsRef<Path>) -> Result<()> {
-    let mut loggers: Vec<Box<dyn SharedLogger>> = vec![TermLogger::new(
-        LevelFilter::Warn,
-        simplelog::Config::default(),
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
+    let dir = dir.as_ref();
+    let (settings, settings_error) = match LogSettings::load(dir) {
+        Ok(settings) => (settings, None),
+        Err(err) => (LogSettings::default(), Some(err)),
+    };
+
+    // Let developers override the file's filter without editing the settings.
+    let file_filter = env::var("RUST_LOG")
+        .ok()
+        .filter(|directives| !directives.trim().is_empty())
+        .unwrap_or_else(|| settings.level.clone());
+    let file_filter = file_filter
+        .parse::<LogFilter>()
+        .map(log_filter::set_file_filter);
+    let terminal_filter = settings
+        .terminal_level
+        .parse::<LogFilter>()
+        .map(log_filter::set_terminal_filter);
+
+    // The wrapped loggers accept every level so that [log_filter] has full
+    // control over what they write.
+    let mut loggers: Vec<Box<dyn SharedLogger>> = vec![Filtered::new(
+        TermLogger::new(
+            LevelFilter::Trace,
+            simplelog::Config::default(),
+            TerminalMode::Mixed,
+            ColorChoice::Auto,
+        ),
+        Sink::Terminal,
     )];
-    if let Ok(logger) = create_write_logger(dir) {
-        loggers.push(logger);
+    if let Ok(logger) = create_write_logger(dir, settings) {
+        loggers.push(Filtered::new(logger, Sink::File));
     }
     CombinedLogger::init(loggers)?;
+
+    // We can't log these until the logger is initialized.
+    if let Some(err) = settings_error {
+        warn!("Failed to load log settings, using defaults: {err:?}");
+    }
+    if let Err(err) = file_filter {
+        warn!("Invalid log level, using the default: {err}");
+    }
+    if let Err(err) = terminal_filter {
+        warn!("Invalid terminal log level, using the default: {err}");
+    }
     Ok(())
 }
 
 /// Creates a write logger that writes to files in [dir].
-fn create_write_logger(dir: impl AsRef<Path>) -> Result<Box<WriteLogger<fs::File>>> {
-    let dir = dir.as_ref().join("log");
-    fs::create_dir_all(&dir)?;
-    let filename = dir.join(Local::now().format("archipelago-%Y-%m-%d.log").to_string());
+fn create_write_logger(dir: &Path, settings: LogSettings) -> Result<Box<WriteLogger<LogFile>>> {
     Ok(WriteLogger::new(
-        LevelFilter::Info,
+        LevelFilter::Trace,
         simplelog::Config::default(),
-        fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(filename)?,
+        LogFile::open(dir.join("log"), settings)?,
     ))
 }
 
@@ -109,37 +154,16 @@ pub fn initialize<G: Game>(blocker: G::InputBlocker) {
     std::thread::spawn(move || {
         info!("Worker thread initialized.");
 
-        let mut core = G::wait_for_system_init().and_then(|_| {
-            info!("Game system initialized.");
-
-            // This mutex isn't strictly necessary since in practice we're only
-            // ever touching this on DS3's main thread. But Rust doesn't have
-            // any way of knowing that and using a Mutex is simpler than
-            // creating a newtype that implements Sync, so we do it anyway.
-            // Because there won't be any contention, it should be very
-            // inexpensive.
-            G::Core::new().map(|core| Arc::new(Mutex::new(core)))
-        });
-
-        if let Ok(core2) = core.as_ref() {
-            let core2 = core2.clone();
-            // Safety: We're playing a little fast and loose here, not
-            // scheduling the task on the main thread. It seems to work, but
-            // really we should probably handle it in the error display.
-            if let Err(err) = unsafe {
-                G::run_recurring_task(move || {
-                    let mut core2 = core2.lock().unwrap();
-                    prof!(core2.base_mut().profiler(), "AP mod logic", {
-                        core2.update(G::is_main_menu());
-                    });
-                })
-            } {
-                core = Err(err);
+        let display = match G::wait_for_system_init() {
+            Ok(()) => {
+                info!("Game system initialized.");
+                ErrorDisplay::<G>::new(create_core::<G>(), blocker)
             }
-        }
+            Err(err) => ErrorDisplay::<G>::fatal(err, blocker),
+        };
 
         if let Err(e) = Hudhook::builder()
-            .with::<G::GraphicsHooks>(ErrorDisplay::<G>::new(core, blocker))
+            .with::<G::GraphicsHooks>(display)
             .build()
             .apply()
         {
@@ -147,3 +171,49 @@ pub fn initialize<G: Game>(blocker: G::InputBlocker) {
         }
     });
 }
+
+/// Creates the mod's core logic and schedules it to be updated every frame.
+/// This may be run again if it fails and the player chooses to retry.
+fn create_core<G: Game>() -> Result<Arc<Mutex<G::Core>>> {
+    // This mutex isn't strictly necessary since in practice we're only ever
+    // touching this on DS3's main thread. But Rust doesn't have any way of
+    // knowing that and using a Mutex is simpler than creating a newtype that
+    // implements Sync, so we do it anyway. Because there won't be any
+    // contention, it should be very inexpensive.
+    let core = Arc::new(Mutex::new(G::Core::new()?));
+
+    let core2 = core.clone();
+    // Safety: We're playing a little fast and loose here, not scheduling the
+    // task on the main thread. It seems to work, but really we should probably
+    // handle it in the error display.
+    unsafe {
+        G::run_recurring_task(move || {
+            let mut core2 = lock_core(&core2);
+            prof!(core2.base_mut().profiler(), "AP mod logic", {
+                if let Err(err) =
+                    panic_guard::catch("the mod's update", || core2.update(G::is_main_menu()))
+                {
+                    core2.base_mut().set_error(err);
+                }
+            });
+
+            if let Some(err) = panic_guard::take_hook_error() {
+                core2.base_mut().set_error(err);
+            }
+        })
+    }?;
+
+    Ok(core)
+}
+
+/// Locks `core`, recovering it if it's been poisoned.
+///
+/// Panics in the update are caught, so the mutex should only be poisoned if
+/// something else panicked while holding it. The core reports its own errors,
+/// so keep going regardless.
+pub(crate) fn lock_core<C>(core: &Mutex<C>) -> MutexGuard<'_, C> {
+    core.lock().unwrap_or_else(|err| {
+        core.clear_poison();
+        err.into_inner()
+    })
+}
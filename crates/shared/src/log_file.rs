@@ -0,0 +1,432 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, mpsc};
+use std::time::SystemTime;
+use std::{process, thread};
+
+use anyhow::{Error, Result};
+use chrono::prelude::*;
+use serde::Deserialize;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+/// The prefix of every text log file's name.
+const PREFIX: &str = "archipelago-";
+
+/// The prefix of every event log file's name. See [crate::event_log].
+pub(crate) const EVENTS_PREFIX: &str = "events-";
+
+/// The extension added to log files once they've been compressed.
+const COMPRESSED_EXTENSION: &str = ".zip";
+
+/// The player's preferences for how log files are written. These are loaded
+/// from `aplog.json` in the mod directory, which is never written by the mod
+/// itself.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub(crate) struct LogSettings {
+    /// The number of log files to keep, including the current one. Older files
+    /// are deleted.
+    pub retention: usize,
+
+    /// The number of event log files to keep, including the current one. These
+    /// are counted separately from [retention] since there's at most one per
+    /// session.
+    pub event_retention: usize,
+
+    /// The size in megabytes at which a log file is closed and a new one is
+    /// started.
+    pub max_file_size_mb: u64,
+
+    /// Whether to compress log files once they're no longer being written.
+    pub compress: bool,
+
+    /// The [crate::log_filter::LogFilter] directives for messages written to
+    /// the log file. The `RUST_LOG` environment variable overrides this if
+    /// it's set.
+    pub level: String,
+
+    /// The [crate::log_filter::LogFilter] directives for messages written to
+    /// the terminal.
+    pub terminal_level: String,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            retention: 20,
+            event_retention: 20,
+            max_file_size_mb: 10,
+            compress: true,
+            level: "info".into(),
+            terminal_level: "warn".into(),
+        }
+    }
+}
+
+impl LogSettings {
+    /// Loads the settings from `dir`, or returns the defaults if the settings
+    /// file doesn't exist.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join("aplog.json");
+        match fs::read_to_string(&path) {
+            Ok(text) => json::from_str(&text).map_err(|err| {
+                Error::from(err).context(format!(
+                    "Failed to parse log settings {}",
+                    path.to_string_lossy()
+                ))
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The maximum size of a log file in bytes.
+    fn max_file_size(&self) -> u64 {
+        self.max_file_size_mb.max(1) * 1024 * 1024
+    }
+}
+
+/// A writer for the mod's log files that starts a new file for each game
+/// session, whenever the current file grows too large, and whenever the date
+/// changes. Old files, including old [crate::event_log] files, are compressed
+/// and pruned in the background.
+///
+/// Each file begins with a marker naming the session it belongs to, so that
+/// every file from a single run of the game can be identified even if it was
+/// split up.
+pub(crate) struct LogFile {
+    /// The directory containing the log files.
+    dir: PathBuf,
+
+    /// The player's log file preferences.
+    settings: LogSettings,
+
+    /// An identifier for this run of the game.
+    session: String,
+
+    /// The number of files that have been opened for this session.
+    part: u32,
+
+    /// The file currently being written.
+    file: File,
+
+    /// The path to [file].
+    path: PathBuf,
+
+    /// The number of bytes written to [file].
+    size: u64,
+
+    /// The local date on which [file] was opened.
+    date: NaiveDate,
+
+    /// Wakes the background thread that compresses and deletes old log files,
+    /// passing it the path of the file that's now being written.
+    cleanup: mpsc::Sender<PathBuf>,
+
+    /// The most recent error from the cleanup thread that hasn't been written
+    /// to the log yet.
+    cleanup_error: Arc<Mutex<Option<Error>>>,
+}
+
+impl LogFile {
+    /// Opens a new log file for this session in `dir`.
+    pub fn open(dir: impl AsRef<Path>, settings: LogSettings) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let now = Local::now();
+        let session = format!("{}-{}", now.format("%Y%m%d%H%M%S"), process::id());
+        let (file, path) = create_file(&dir, now)?;
+        let (cleanup, cleanup_error) = spawn_cleanup_thread(&dir, &settings, now.into());
+        let mut log_file = Self {
+            dir,
+            settings,
+            session,
+            part: 1,
+            file,
+            path,
+            size: 0,
+            date: now.date_naive(),
+            cleanup,
+            cleanup_error,
+        };
+        log_file.write_marker(now)?;
+        log_file.clean_up();
+        Ok(log_file)
+    }
+
+    /// Closes the current file and starts a new one.
+    fn rotate(&mut self) -> io::Result<()> {
+        let now = Local::now();
+        let (file, path) = create_file(&self.dir, now)?;
+        self.file = file;
+        self.path = path;
+        self.size = 0;
+        self.date = now.date_naive();
+        self.part += 1;
+        self.write_marker(now)?;
+        self.clean_up();
+        Ok(())
+    }
+
+    /// Writes a line to the beginning of the current file identifying the
+    /// session it belongs to.
+    fn write_marker(&mut self, now: DateTime<Local>) -> io::Result<()> {
+        let marker = format!(
+            "===== Session {} part {} started {} =====\n",
+            self.session,
+            self.part,
+            now.to_rfc3339()
+        );
+        self.file.write_all(marker.as_bytes())?;
+        self.size += marker.len() as u64;
+        Ok(())
+    }
+
+    /// Asks the cleanup thread to compress and delete old log files.
+    fn clean_up(&self) {
+        // The thread only exits if it panicked, in which case there's nothing
+        // more we can do.
+        let _ = self.cleanup.send(self.path.clone());
+    }
+
+    /// Writes the last error from the cleanup thread to the log, if there is
+    /// one.
+    ///
+    /// This can't go through [log::warn] because it's called while the logger
+    /// is already writing, so it writes a warning line of its own instead.
+    fn write_cleanup_error(&mut self) -> io::Result<()> {
+        let Some(err) = self.cleanup_error.lock().unwrap().take() else {
+            return Ok(());
+        };
+
+        let line = format!(
+            "{} [WARN] Failed to clean up old log files: {err:?}\n",
+            Local::now().format("%H:%M:%S")
+        );
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0
+            && (self.size + buf.len() as u64 > self.settings.max_file_size()
+                || Local::now().date_naive() != self.date)
+        {
+            self.rotate()?;
+        }
+        self.write_cleanup_error()?;
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Creates a new log file in `dir` named after `now`.
+fn create_file(dir: &Path, now: DateTime<Local>) -> io::Result<(File, PathBuf)> {
+    let stem = format!("{PREFIX}{}", now.format("%Y-%m-%d-%H-%M-%S"));
+    let mut path = dir.join(format!("{stem}.log"));
+    // If we rotate more than once a second, add a suffix to keep the names
+    // unique.
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = dir.join(format!("{stem}-{suffix}.log"));
+    }
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    Ok((file, path))
+}
+
+/// Starts the thread that cleans up old log files in `dir` each time it's sent
+/// the path of the file that's currently being written. Errors are stored in
+/// the returned slot so that they can be written to the log.
+///
+/// There's only ever one cleanup thread per session, so cleanups never race
+/// each other. If it's woken several times while it's busy, it only cleans up
+/// once more.
+fn spawn_cleanup_thread(
+    dir: &Path,
+    settings: &LogSettings,
+    session_start: SystemTime,
+) -> (mpsc::Sender<PathBuf>, Arc<Mutex<Option<Error>>>) {
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+    let error = Arc::new(Mutex::new(None));
+
+    let dir = dir.to_path_buf();
+    let settings = settings.clone();
+    let error2 = error.clone();
+    thread::spawn(move || {
+        // Every file this session has written, which are safe to clean up once
+        // they're no longer current.
+        let mut own_files = HashSet::new();
+        while let Ok(mut current) = receiver.recv() {
+            own_files.insert(current.clone());
+            while let Ok(newer) = receiver.try_recv() {
+                own_files.insert(newer.clone());
+                current = newer;
+            }
+
+            let is_old = |path: &Path, modified: SystemTime| {
+                path != current
+                    && (modified < session_start || own_files.contains(&uncompressed_path(path)))
+            };
+            if let Err(err) = clean_up(&dir, &settings, is_old) {
+                *error2.lock().unwrap() = Some(err);
+            }
+        }
+    });
+
+    (sender, error)
+}
+
+/// Compresses old text and event log files in `dir` if the settings ask for
+/// it, then deletes the oldest files of each kind beyond its retention limit.
+///
+/// Only files for which `is_old` returns true, given their path and
+/// modification time, are touched, and only if nothing else has them open.
+/// This leaves alone the active files of another running game that shares the
+/// mod directory, as well as files still locked by a session that crashed.
+fn clean_up(
+    dir: &Path,
+    settings: &LogSettings,
+    is_old: impl Fn(&Path, SystemTime) -> bool,
+) -> Result<()> {
+    clean_up_files(log_files(dir)?, settings.retention, settings, &is_old)?;
+    clean_up_files(
+        event_log_files(dir)?,
+        settings.event_retention,
+        settings,
+        &is_old,
+    )
+}
+
+/// Compresses `files` if the settings ask for it, then deletes the oldest ones
+/// beyond `retention`. See [clean_up].
+fn clean_up_files(
+    files: Vec<PathBuf>,
+    retention: usize,
+    settings: &LogSettings,
+    is_old: impl Fn(&Path, SystemTime) -> bool,
+) -> Result<()> {
+    let mut files = files
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by_key(|(_, modified)| *modified);
+    let excess = files.len().saturating_sub(retention.max(1));
+
+    for (i, (path, modified)) in files.into_iter().enumerate() {
+        if !is_old(&path, modified) || is_in_use(&path) {
+            continue;
+        }
+
+        if i < excess {
+            fs::remove_file(&path)?;
+        } else if settings.compress && !is_compressed(&path) {
+            compress(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether another handle to the file at `path` is open, which means
+/// another process may still be writing to it.
+#[cfg(windows)]
+fn is_in_use(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // Opening with no sharing fails if anything else has the file open.
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .share_mode(0)
+        .open(path)
+        .is_err()
+}
+
+/// Returns whether another handle to the file at `path` is open. There's no
+/// cheap way to tell outside Windows, so this assumes it isn't.
+#[cfg(not(windows))]
+fn is_in_use(_path: &Path) -> bool {
+    false
+}
+
+/// Returns the paths of all text log files in `dir`, compressed or not.
+pub(crate) fn log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    files_named(dir, PREFIX, ".log")
+}
+
+/// Returns the paths of all event log files in `dir`, compressed or not.
+fn event_log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    files_named(dir, EVENTS_PREFIX, ".jsonl")
+}
+
+/// Returns the paths of all files in `dir` whose names start with `prefix` and
+/// end with `extension`, with or without [COMPRESSED_EXTENSION] after it.
+fn files_named(dir: &Path, prefix: &str, extension: &str) -> io::Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+
+    let compressed_extension = format!("{extension}{COMPRESSED_EXTENSION}");
+    Ok(entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with(prefix)
+                    && (name.ends_with(extension) || name.ends_with(&compressed_extension))
+            })
+        })
+        .collect())
+}
+
+/// Returns whether `path` is a compressed log file.
+fn is_compressed(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(COMPRESSED_EXTENSION)
+}
+
+/// Returns the path of the log file that `path` was compressed from, or `path`
+/// itself if it isn't compressed.
+fn uncompressed_path(path: &Path) -> PathBuf {
+    let path = path.to_string_lossy();
+    PathBuf::from(path.strip_suffix(COMPRESSED_EXTENSION).unwrap_or(&path))
+}
+
+/// Compresses the log file at `path` into a zip archive alongside it, deletes
+/// the original, and returns the path to the archive.
+fn compress(path: &Path) -> Result<PathBuf> {
+    let mut archive_path = path.as_os_str().to_owned();
+    archive_path.push(COMPRESSED_EXTENSION);
+    let archive_path = PathBuf::from(archive_path);
+
+    let mut zip = ZipWriter::new(File::create(&archive_path)?);
+    zip.start_file(
+        path.file_name().unwrap().to_string_lossy(),
+        SimpleFileOptions::default().compression_method(CompressionMethod::Deflated),
+    )?;
+    io::copy(&mut File::open(path)?, &mut zip)?;
+    zip.finish()?;
+
+    fs::remove_file(path)?;
+    Ok(archive_path)
+}
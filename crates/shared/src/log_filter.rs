@@ -0,0 +1,324 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use anyhow::{Error, Result, bail};
+use log::*;
+use simplelog::{Config, SharedLogger};
+
+/// The filter for messages written to the log file.
+static FILE_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::new(LevelFilter::Info));
+
+/// The filter for messages written to the terminal.
+static TERMINAL_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::new(LevelFilter::Warn));
+
+/// A set of [RUST_LOG]-style directives that determine which log messages are
+/// written, such as `info,ds3_archipelago::item=debug,tungstenite=off`.
+///
+/// Each comma-separated directive is either a bare level, which applies to
+/// every target not otherwise mentioned, or `target=level`, which applies to
+/// `target` and all modules within it. A bare target enables every level for
+/// it. When several directives match, the most specific one wins.
+///
+/// [RUST_LOG]: https://docs.rs/env_logger/latest/env_logger/#enabling-logging
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LogFilter {
+    /// The level for targets that don't match any of [directives].
+    default: LevelFilter,
+
+    /// Targets and the levels for each of them.
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// Returns a filter that allows messages at `level` and above from every
+    /// target.
+    const fn new(level: LevelFilter) -> Self {
+        Self {
+            default: level,
+            directives: vec![],
+        }
+    }
+
+    /// Returns whether a message with `metadata` should be written.
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let target = metadata.target();
+        let level = self
+            .directives
+            .iter()
+            .filter(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level);
+        metadata.level() <= level
+    }
+
+    /// Returns the most verbose level that any target allows.
+    fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = Error;
+
+    fn from_str(directives: &str) -> Result<Self> {
+        let mut filter = LogFilter::new(LevelFilter::Off);
+        let mut has_default = false;
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let Ok(level) = level.trim().parse() else {
+                        bail!(
+                            "Invalid log level \"{}\" for {}",
+                            level.trim(),
+                            target.trim()
+                        );
+                    };
+                    filter.directives.push((target.trim().to_string(), level));
+                }
+                None => match directive.parse() {
+                    Ok(level) => {
+                        filter.default = level;
+                        has_default = true;
+                    }
+                    // A bare target enables all logging for that target.
+                    Err(_) => filter
+                        .directives
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+
+        // Like env_logger, only log errors by default if any targets are
+        // specified.
+        if !has_default && !filter.directives.is_empty() {
+            filter.default = LevelFilter::Error;
+        } else if !has_default {
+            bail!("No log directives given");
+        }
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default.as_str().to_lowercase())?;
+        for (target, level) in &self.directives {
+            write!(f, ",{target}={}", level.as_str().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the filter for messages written to the log file.
+pub(crate) fn file_filter() -> LogFilter {
+    FILE_FILTER.read().unwrap().clone()
+}
+
+/// Sets the filter for messages written to the log file.
+pub(crate) fn set_file_filter(filter: LogFilter) {
+    *FILE_FILTER.write().unwrap() = filter;
+    update_max_level();
+}
+
+/// Returns the filter for messages written to the terminal.
+pub(crate) fn terminal_filter() -> LogFilter {
+    TERMINAL_FILTER.read().unwrap().clone()
+}
+
+/// Sets the filter for messages written to the terminal.
+pub(crate) fn set_terminal_filter(filter: LogFilter) {
+    *TERMINAL_FILTER.write().unwrap() = filter;
+    update_max_level();
+}
+
+/// Tells the `log` crate the most verbose level any logger might write, so
+/// that it can skip formatting messages that will never be written.
+fn update_max_level() {
+    log::set_max_level(
+        FILE_FILTER
+            .read()
+            .unwrap()
+            .max_level()
+            .max(TERMINAL_FILTER.read().unwrap().max_level()),
+    );
+}
+
+/// Which of the global filters a [Filtered] logger uses.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Sink {
+    /// The log file.
+    File,
+
+    /// The terminal.
+    Terminal,
+}
+
+impl Sink {
+    /// Returns the filter for this sink.
+    fn filter(&self) -> &'static RwLock<LogFilter> {
+        match self {
+            Sink::File => &FILE_FILTER,
+            Sink::Terminal => &TERMINAL_FILTER,
+        }
+    }
+}
+
+/// A wrapper around another logger that only passes along messages allowed by
+/// the filter for [sink]. The wrapped logger should accept every level so that
+/// the filter can be changed at runtime.
+pub(crate) struct Filtered<L: SharedLogger> {
+    /// The logger to pass allowed messages to.
+    inner: Box<L>,
+
+    /// Which filter to apply.
+    sink: Sink,
+}
+
+impl<L: SharedLogger> Filtered<L> {
+    /// Wraps `inner` so that it's filtered according to `sink`.
+    pub fn new(inner: Box<L>, sink: Sink) -> Box<Self> {
+        Box::new(Self { inner, sink })
+    }
+}
+
+impl<L: SharedLogger> Log for Filtered<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.sink.filter().read().unwrap().enabled(metadata) && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self
+            .sink
+            .filter()
+            .read()
+            .unwrap()
+            .enabled(record.metadata())
+        {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+impl<L: SharedLogger + 'static> SharedLogger for Filtered<L> {
+    fn level(&self) -> LevelFilter {
+        self.sink.filter().read().unwrap().max_level()
+    }
+
+    fn config(&self) -> Option<&Config> {
+        self.inner.config()
+    }
+
+    fn as_log(self: Box<Self>) -> Box<dyn Log> {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns whether `filter` allows a message at `level` from `target`.
+    fn allows(filter: &LogFilter, level: Level, target: &str) -> bool {
+        filter.enabled(&Metadata::builder().level(level).target(target).build())
+    }
+
+    #[test]
+    fn parses_bare_level() {
+        let filter: LogFilter = "debug".parse().unwrap();
+        assert_eq!(filter, LogFilter::new(LevelFilter::Debug));
+    }
+
+    #[test]
+    fn parses_target_levels() {
+        let filter: LogFilter = " info, ds3::item = debug ,tungstenite=off".parse().unwrap();
+        assert_eq!(filter.default, LevelFilter::Info);
+        assert_eq!(
+            filter.directives,
+            vec![
+                ("ds3::item".to_string(), LevelFilter::Debug),
+                ("tungstenite".to_string(), LevelFilter::Off),
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_target_enables_trace() {
+        let filter: LogFilter = "warn,ds3".parse().unwrap();
+        assert_eq!(
+            filter.directives,
+            vec![("ds3".to_string(), LevelFilter::Trace)]
+        );
+    }
+
+    #[test]
+    fn defaults_to_error_with_only_targets() {
+        let filter: LogFilter = "ds3=info".parse().unwrap();
+        assert_eq!(filter.default, LevelFilter::Error);
+    }
+
+    #[test]
+    fn rejects_invalid_level() {
+        assert!("ds3=loud".parse::<LogFilter>().is_err());
+    }
+
+    #[test]
+    fn rejects_empty_directives() {
+        assert!("".parse::<LogFilter>().is_err());
+        assert!(" , ".parse::<LogFilter>().is_err());
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        let filter: LogFilter = "info,ds3::item=debug".parse().unwrap();
+        assert_eq!(filter.to_string(), "info,ds3::item=debug");
+        assert_eq!(filter.to_string().parse::<LogFilter>().unwrap(), filter);
+    }
+
+    #[test]
+    fn uses_default_for_unmatched_targets() {
+        let filter: LogFilter = "warn,ds3=trace".parse().unwrap();
+        assert!(allows(&filter, Level::Warn, "hudhook"));
+        assert!(!allows(&filter, Level::Info, "hudhook"));
+    }
+
+    #[test]
+    fn most_specific_directive_wins() {
+        let filter: LogFilter = "warn,ds3=info,ds3::item=off".parse().unwrap();
+        assert!(allows(&filter, Level::Info, "ds3"));
+        assert!(allows(&filter, Level::Info, "ds3::core"));
+        assert!(!allows(&filter, Level::Debug, "ds3::core"));
+        assert!(!allows(&filter, Level::Error, "ds3::item"));
+        assert!(!allows(&filter, Level::Error, "ds3::item::grant"));
+    }
+
+    #[test]
+    fn matches_only_whole_path_segments() {
+        let filter: LogFilter = "warn,ds3=trace".parse().unwrap();
+        assert!(allows(&filter, Level::Trace, "ds3::item"));
+        assert!(!allows(&filter, Level::Trace, "ds3_archipelago"));
+    }
+
+    #[test]
+    fn max_level_covers_every_directive() {
+        let filter: LogFilter = "warn,ds3=debug,tungstenite=off".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+        let filter: LogFilter = "info".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Info);
+    }
+}
@@ -6,30 +6,37 @@ use std::{marker::PhantomData, mem, ptr};
 use archipelago_rs::{self as ap, RichText, TextColor};
 use hudhook::RenderContext;
 use imgui::*;
-use imgui_sys::igSetWindowFocus_Str;
+use imgui_sys::{ImGuiCond_Always, igSetNextWindowDockID, igSetWindowFocus_Str};
 use log::*;
 use regex_macro::regex;
 
-use crate::{Core, Game, prof};
+use crate::{Core, DeathLinkDirection, DeathLinkMode, Game, l10n, l10n::Locale, prof, tr, utils};
 
+mod classification;
+mod fonts;
+mod gamepad;
+mod link;
+mod log_layout;
+mod settings;
 mod text_input_history;
-
+mod theme;
+
+use classification::ItemClassification;
+pub(crate) use fonts::Fonts;
+use gamepad::Gamepad;
+use link::LinkAction;
+use log_layout::LogLayout;
+pub(crate) use settings::OverlaySettings;
 use text_input_history::TextInputHistory;
+use theme::{Palette, Rgb, Theme};
 
-/// The duration between debug prints of the frame timing data.
-#[cfg(feature = "profile")]
-const TIME_PER_FRAME_PRINT: Duration = Duration::from_secs(10);
-
-const GREEN: ImColor32 = ImColor32::from_rgb(0x8A, 0xE2, 0x43);
-const RED: ImColor32 = ImColor32::from_rgb(0xFF, 0x44, 0x44);
-const WHITE: ImColor32 = ImColor32::from_rgb(0xFF, 0xFF, 0xFF);
-// This is the darkest gray that still meets WCAG guidelines for contrast with
-// the black background of the overlay.
-const BLACK: ImColor32 = ImColor32::from_rgb(0x9C, 0x9C, 0x9C);
-const YELLOW: ImColor32 = ImColor32::from_rgb(0xFC, 0xE9, 0x4F);
-const BLUE: ImColor32 = ImColor32::from_rgb(0x82, 0xA9, 0xD4);
-const MAGENTA: ImColor32 = ImColor32::from_rgb(0xBF, 0x9B, 0xBC);
-const CYAN: ImColor32 = ImColor32::from_rgb(0x34, 0xE2, 0xE2);
+/// The default height of the main overlay window.
+const MAIN_WINDOW_HEIGHT: f32 = 300.;
+
+/// The color of text drawn on top of a background color. All the palette colors
+/// are light enough to contrast with the overlay's black background, so pure
+/// black has plenty of contrast with them in turn.
+const BACKGROUND_TEXT: ImColor32 = ImColor32::BLACK;
 
 /// The visual overlay that appears on top of the game.
 pub struct Overlay<G: Game> {
@@ -46,9 +53,15 @@ pub struct Overlay<G: Game> {
     /// The history of messages sent to the say input.
     say_history: TextInputHistory,
 
+    /// The log messages wrapped to fit the log window.
+    log_layout: LogLayout,
+
     /// Whether the log was previously scrolled all the way down.
     log_was_scrolled_down: bool,
 
+    /// The name of the player whose messages the log is filtered to, if any.
+    log_filter: Option<String>,
+
     /// The time of the most recent log we've seen. This is used to determine
     /// when new logs are emitted for [frames_since_new_logs].
     last_log_emitted: Instant,
@@ -57,31 +70,44 @@ pub struct Overlay<G: Game> {
     /// We use this to determine when to auto-scroll the log window.
     frames_since_new_logs: u64,
 
-    /// The current font scale for the overlay UI.
-    font_scale: f32,
+    /// The fonts loaded into the imgui context, or None if [initialize] hasn't
+    /// been called yet.
+    fonts: Option<Fonts>,
+
+    /// The player's persistent overlay preferences.
+    settings: OverlaySettings,
 
-    /// The unfocused window opacity for the overlay UI.
-    unfocused_window_opacity: f32,
+    /// The player's controller, which can be used to navigate the overlay.
+    gamepad: Gamepad,
 
     /// Whether the settings window is currently visible.
     settings_window_visible: bool,
 
+    /// Whether the player chose to dismiss a save problem that would throw
+    /// away their save data and is being asked to confirm it.
+    confirm_dismiss: bool,
+
+    /// The error from the last failed attempt to restore a backup for the
+    /// current save problem, if any.
+    restore_error: Option<String>,
+
+    /// Whether the profiler window is visible.
+    #[cfg(feature = "profile")]
+    profiler_window_visible: bool,
+
     /// Whether the game was on the main menu in the previous frame.
     was_main_menu: bool,
 
     /// Whether the overlay window was focused in the previous frame.
     was_window_focused: bool,
 
-    /// Whether compact mode was enabled in the previous frame.
-    was_compact_mode: bool,
-
     /// Whether to focus the say input on the next frame. Used to keep focus
     /// after the user pressed enter.
     focus_say_input_next_frame: bool,
 
-    /// The size of the main overlay window in the previous frame. Used to
-    /// resize when entering and exiting compact mode.
-    previous_size: Option<[f32; 2]>,
+    /// Whether to move all the overlay's windows back to their default
+    /// positions and sizes on the next frame.
+    reset_layout_next_frame: bool,
 
     /// The time the last profile data was printed.
     #[cfg(feature = "profile")]
@@ -101,50 +127,108 @@ unsafe impl<G: Game> Sync for Overlay<G> {}
 impl<G: Game> Overlay<G> {
     /// Creates a new instance of the overlay and the core mod logic.
     pub fn new() -> Self {
+        let settings = OverlaySettings::load();
+        l10n::set_locale(settings.locale);
+
         Self {
-            font_scale: 1.8,
-            unfocused_window_opacity: 0.4,
-            was_compact_mode: true,
+            settings,
 
             // Default values. We can't use [Default::default] because G doesn't
             // require `Default`.
             viewport_size: None,
+            fonts: None,
+            gamepad: Default::default(),
             popup_url: Default::default(),
             say_input: Default::default(),
             say_history: Default::default(),
+            log_layout: Default::default(),
             log_was_scrolled_down: false,
+            log_filter: None,
             last_log_emitted: Instant::now(),
             frames_since_new_logs: 0,
             settings_window_visible: false,
+            confirm_dismiss: false,
+            restore_error: None,
+            #[cfg(feature = "profile")]
+            profiler_window_visible: false,
             was_main_menu: false,
             was_window_focused: false,
             focus_say_input_next_frame: false,
-            previous_size: None,
+            reset_layout_next_frame: false,
             #[cfg(feature = "profile")]
             last_profile_printed: Instant::now(),
             _marker: PhantomData,
         }
     }
 
+    /// See [ImguiRenderLoop::initialize]. This takes ownership of the fonts
+    /// loaded into `ctx`, enables gamepad navigation and docking, and points
+    /// imgui at the file in which to persist the window layout.
+    pub fn initialize(&mut self, ctx: &mut Context, fonts: Fonts) {
+        self.fonts = Some(fonts);
+
+        // If we can't find the mod directory, the layout just won't persist
+        // across sessions.
+        ctx.set_ini_filename(
+            utils::mod_directory()
+                .ok()
+                .map(|dir| dir.join("apoverlay.ini")),
+        );
+
+        let io = ctx.io_mut();
+        io.config_flags.insert(ConfigFlags::NAV_ENABLE_GAMEPAD);
+        io.config_flags.insert(ConfigFlags::DOCKING_ENABLE);
+        io.backend_flags.insert(BackendFlags::HAS_GAMEPAD);
+    }
+
+    /// Returns whether the overlay currently has control of the player's
+    /// controller, in which case the game shouldn't receive its input.
+    pub fn wants_gamepad(&self) -> bool {
+        self.gamepad.is_active()
+    }
+
     /// Like [ImguiRenderLoop::render], but takes a reference to [Core] as well.
     ///
     /// We don't store `core` directly in the overlay so that we can ensure that
     /// its mutex is only locked once per render.
     pub fn render(&mut self, ui: &mut Ui, core: &mut G::Core) {
+        // Let the player dock windows to the edges of the screen. The central
+        // node is transparent to both rendering and input so the game remains
+        // visible and playable.
+        ui.dockspace_over_main_viewport();
+        let reset_layout = mem::take(&mut self.reset_layout_next_frame);
+
         prof!(core.base_mut().profiler(), "AP overlay", {
             prof!(core.base_mut().profiler(), "main window", {
-                self.render_main_window(ui, core);
+                self.render_main_window(ui, core, reset_layout);
             });
 
             prof!(core.base_mut().profiler(), "settings window", {
-                self.render_settings_window(ui);
+                self.render_settings_window(ui, reset_layout);
+            });
+
+            prof!(core.base_mut().profiler(), "save problem", {
+                self.render_save_problem_popup(ui, core);
+            });
+
+            #[cfg(feature = "profile")]
+            prof!(core.base_mut().profiler(), "profiler window", {
+                self.render_profiler_window(ui, core, reset_layout);
             });
         });
 
+        // Give control of the controller back to the game once the player
+        // leaves the overlay, however that happens.
+        if !ui.is_window_focused_with_flags(WindowFocusedFlags::ANY_WINDOW) {
+            self.gamepad.set_active(false);
+        }
+
         #[cfg(feature = "profile")]
         {
+            core.base_mut().profiler().end_frame();
+
             let now = Instant::now();
-            if now.duration_since(self.last_profile_printed) >= TIME_PER_FRAME_PRINT {
+            if now.duration_since(self.last_profile_printed) >= crate::TIME_PER_FRAME_PRINT {
                 core.base_mut().profiler().report();
                 self.last_profile_printed = now;
             }
@@ -156,7 +240,7 @@ impl<G: Game> Overlay<G> {
     pub fn before_render<'a>(
         &'a mut self,
         ctx: &mut Context,
-        _render_context: &'a mut dyn RenderContext,
+        render_context: &'a mut dyn RenderContext,
     ) {
         self.frames_since_new_logs += 1;
         self.viewport_size = match ctx.main_viewport().size {
@@ -166,15 +250,26 @@ impl<G: Game> Overlay<G> {
 
         // Set the font scale here because we need the frame height later to
         // calculate the main window size, which depends on it.
-        ctx.io_mut().font_global_scale = self.font_scale;
+        ctx.io_mut().font_global_scale = self.settings.font_scale;
+
+        if let Some(fonts) = self.fonts.as_mut() {
+            fonts.rebuild_if_needed(ctx, render_context);
+        }
+
+        self.gamepad.poll();
+        self.gamepad.feed(ctx.io_mut());
     }
 
     /// Render the primary overlay window and any popups it opens.
-    fn render_main_window(&mut self, ui: &Ui, core: &mut G::Core) {
+    ///
+    /// If `reset_layout` is true, this moves the window back to its default
+    /// position and size.
+    fn render_main_window(&mut self, ui: &Ui, core: &mut G::Core, reset_layout: bool) {
         let Some(viewport_size) = self.viewport_size else {
             return;
         };
 
+        let mut focus_window = false;
         prof!(core.base_mut().profiler(), "set focus", {
             // By default, imgui doesn't remove focus when escape is pressed,
             // even though it does relinquish its claim to the mouse and
@@ -190,12 +285,24 @@ impl<G: Game> Overlay<G> {
             {
                 unsafe { igSetWindowFocus_Str(ptr::null()) };
             }
+
+            // Controller players press a chord to move focus into the overlay
+            // and again to return it to the game.
+            if self.gamepad.chord_pressed() {
+                if self.gamepad.is_active() {
+                    self.gamepad.set_active(false);
+                    unsafe { igSetWindowFocus_Str(ptr::null()) };
+                } else {
+                    self.gamepad.set_active(true);
+                    focus_window = true;
+                }
+            }
         });
 
         let window_opacity = if self.was_window_focused {
             1.0
         } else {
-            self.unfocused_window_opacity
+            self.settings.unfocused_window_opacity
         };
         let mut bg_color = [0.0, 0.0, 0.0, window_opacity];
         let _bg = ui.push_style_color(StyleColor::WindowBg, bg_color);
@@ -203,52 +310,43 @@ impl<G: Game> Overlay<G> {
         bg_color[3] = 1.0; // Popup backgrounds should always be fully opaque.
         let _popup_bg = ui.push_style_color(StyleColor::PopupBg, bg_color);
 
-        let mut builder = ui
+        let layout_condition = if reset_layout {
+            Condition::Always
+        } else {
+            Condition::FirstUseEver
+        };
+
+        let builder = ui
             .window(format!(
-                "Archipelago Client {} [{}]###ap-client-overlay",
-                G::CLIENT_VERSION,
-                match core.base().connection_state_type() {
-                    ap::ConnectionStateType::Connected => "Connected",
-                    ap::ConnectionStateType::Connecting => "Connecting...",
-                    ap::ConnectionStateType::Disconnected => "Disconnected",
-                }
+                "{}###ap-client-overlay",
+                tr!(
+                    "overlay-title",
+                    version = G::CLIENT_VERSION,
+                    status = match core.base().connection_state_type() {
+                        ap::ConnectionStateType::Connected => tr!("status-connected"),
+                        ap::ConnectionStateType::Connecting => tr!("status-connecting"),
+                        ap::ConnectionStateType::Disconnected => tr!("status-disconnected"),
+                    },
+                )
             ))
-            .position([viewport_size[0] - 30., 30.], Condition::FirstUseEver)
+            .position([viewport_size[0] - 30., 30.], layout_condition)
             .position_pivot([1., 0.])
+            .size(
+                [viewport_size[0] * 0.4, MAIN_WINDOW_HEIGHT],
+                layout_condition,
+            )
+            .focused(focus_window)
             .menu_bar(true);
 
-        // When the menu opens or closes, add or remove space from the bottom of
-        // the overlay for the message bar and horizontal scrollbar.
-        let is_compact_mode = self.is_compact_mode(core);
-        builder = match (self.previous_size, is_compact_mode, self.was_compact_mode) {
-            (Some(size), true, false) => {
-                let style = ui.clone_style();
-                let remove_bottom_space =
-                    ui.frame_height() + style.window_padding[1] + style.scrollbar_size;
-
-                builder.size(
-                    [size[0], size[1] - remove_bottom_space.ceil()],
-                    Condition::Always,
-                )
-            }
-            (Some(size), false, true) => {
-                let style = ui.clone_style();
-                let add_bottom_space =
-                    ui.frame_height() + style.window_padding[1] + style.scrollbar_size;
-
-                builder.size(
-                    [size[0], size[1] + add_bottom_space.ceil()],
-                    Condition::Always,
-                )
-            }
-            _ => builder.size([viewport_size[0] * 0.4, 300.], Condition::FirstUseEver),
-        };
+        if reset_layout {
+            // Safety: This is only ever run on the main thread during a frame.
+            unsafe { igSetNextWindowDockID(0, ImGuiCond_Always as i32) };
+        }
 
-        let focus_say_input = mem::take(&mut self.focus_say_input_next_frame);
         let collapsed = builder
             .build(|| {
                 prof!(core.base_mut().profiler(), "menu bar", {
-                    self.render_menu_bar(ui);
+                    self.render_menu_bar(ui, core);
                 });
 
                 ui.separator();
@@ -257,33 +355,76 @@ impl<G: Game> Overlay<G> {
                     self.render_log_window(ui, core);
                 });
 
-                if !is_compact_mode {
-                    if core.base().is_disconnected() {
-                        prof!(core.base_mut().profiler(), "connection buttons", {
-                            self.render_connection_buttons(ui, core);
-                        });
-                    } else {
-                        prof!(core.base_mut().profiler(), "say input", {
-                            self.render_say_input(ui, core, focus_say_input);
-                        });
-                    }
-                }
-                prof!(core.base_mut().profiler(), "URL modal", {
-                    self.render_url_modal_popup(ui, core);
-                });
-
                 self.was_window_focused =
                     ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS);
-                self.previous_size = Some(ui.window_size());
             })
             .is_none();
 
         self.was_main_menu = unsafe { G::is_main_menu() };
-        self.was_compact_mode = is_compact_mode;
 
         if collapsed {
             self.was_window_focused = false;
         }
+
+        // Render this while the main window's colors are still pushed so that
+        // the two windows fade in and out together.
+        prof!(core.base_mut().profiler(), "input window", {
+            self.render_input_window(ui, core, reset_layout);
+        });
+    }
+
+    /// Renders the window containing the say input, or the buttons to
+    /// reconnect while the client is disconnected. This is separate from the
+    /// main window so that the player can dock it on its own. It's hidden in
+    /// compact mode.
+    ///
+    /// If `reset_layout` is true, this moves the window back to its default
+    /// position and size, just below the main window.
+    fn render_input_window(&mut self, ui: &Ui, core: &mut G::Core, reset_layout: bool) {
+        let focus_say_input = mem::take(&mut self.focus_say_input_next_frame);
+        let Some(viewport_size) = self.viewport_size else {
+            return;
+        };
+        if self.is_compact_mode(core) {
+            return;
+        }
+
+        let layout_condition = if reset_layout {
+            Condition::Always
+        } else {
+            Condition::FirstUseEver
+        };
+        if reset_layout {
+            // Safety: This is only ever run on the main thread during a frame.
+            unsafe { igSetNextWindowDockID(0, ImGuiCond_Always as i32) };
+        }
+
+        ui.window(format!("{}###ap-overlay-input", tr!("input-title")))
+            .position(
+                [viewport_size[0] - 30., 30. + MAIN_WINDOW_HEIGHT + 10.],
+                layout_condition,
+            )
+            .position_pivot([1., 0.])
+            // A height of 0 fits the window to its contents.
+            .size([viewport_size[0] * 0.4, 0.], layout_condition)
+            .collapsible(false)
+            .build(|| {
+                if core.base().is_disconnected() {
+                    prof!(core.base_mut().profiler(), "connection buttons", {
+                        self.render_connection_buttons(ui, core);
+                    });
+                } else {
+                    prof!(core.base_mut().profiler(), "say input", {
+                        self.render_say_input(ui, core, focus_say_input);
+                    });
+                }
+                prof!(core.base_mut().profiler(), "URL modal", {
+                    self.render_url_modal_popup(ui, core);
+                });
+
+                self.was_window_focused |=
+                    ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS);
+            });
     }
 
     /// Renders the modal popup which queries the player for connection
@@ -296,15 +437,15 @@ impl<G: Game> Overlay<G> {
             .always_auto_resize(true)
             .build(|| {
                 {
-                    let _item_width = ui.push_item_width(500. * self.font_scale);
-                    ui.input_text("Room URL", &mut self.popup_url)
+                    let _item_width = ui.push_item_width(500. * self.settings.font_scale);
+                    ui.input_text(tr!("room-url"), &mut self.popup_url)
                         .hint("archipelago.gg:12345")
                         .chars_noblank(true)
                         .build();
                 }
 
                 ui.disabled(self.popup_url.is_empty(), || {
-                    if ui.button("Connect") {
+                    if ui.button(tr!("connect")) {
                         ui.close_current_popup();
                         if let Err(e) = core.base_mut().update_url(&self.popup_url) {
                             error!("Failed to save config: {e}");
@@ -314,18 +455,211 @@ impl<G: Game> Overlay<G> {
             });
     }
 
+    /// Renders the modal popup which tells the player about a problem with
+    /// their save data and offers to restore a backup.
+    fn render_save_problem_popup(&mut self, ui: &Ui, core: &mut G::Core) {
+        let Some(store) = core.save_store() else {
+            return;
+        };
+        let Some(problem) = store.problem() else {
+            self.confirm_dismiss = false;
+            self.restore_error = None;
+            return;
+        };
+
+        let mut restore = false;
+        let mut dismiss = false;
+        let red = self.settings.palette().red;
+        ui.open_popup("#save-problem");
+        ui.modal_popup_config("#save-problem")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .size([600. * self.settings.font_scale, 0.], Condition::Always)
+            .build(|| {
+                ui.text_wrapped(&problem.message);
+                ui.separator();
+                match &problem.backup {
+                    Some(backup) => ui.text_wrapped(tr!(
+                        if problem.any_character {
+                            "backup-info-any-character"
+                        } else {
+                            "backup-info"
+                        },
+                        path = backup.path.to_string_lossy(),
+                        items_granted = backup.items_granted,
+                        locations = backup.locations,
+                    )),
+                    None if problem.any_character => {
+                        ui.text_wrapped(tr!("no-backup-any-character"))
+                    }
+                    None => ui.text_wrapped(tr!("no-backup")),
+                }
+                if let Some(err) = &self.restore_error {
+                    ui.text_colored(red.to_im().to_rgba_f32s(), err);
+                }
+
+                ui.separator();
+                if self.confirm_dismiss {
+                    // Dismissing unreadable data can't be undone once the game
+                    // saves, so make sure the player knows what they're giving
+                    // up.
+                    ui.text_wrapped(tr!("dismiss-discards-save"));
+                    dismiss = ui.button(tr!("dismiss-anyway"));
+                    ui.same_line();
+                    if ui.button(tr!("cancel")) {
+                        self.confirm_dismiss = false;
+                    }
+                } else {
+                    if problem.backup.is_some() {
+                        restore = ui.button(tr!("restore-backup"));
+                        ui.same_line();
+                    }
+                    if ui.button(tr!("dismiss")) {
+                        if problem.discards_save {
+                            self.confirm_dismiss = true;
+                        } else {
+                            dismiss = true;
+                        }
+                    }
+                }
+                if restore || dismiss {
+                    ui.close_current_popup();
+                }
+            });
+
+        if dismiss {
+            store.dismiss_problem();
+        } else if restore && let Some(backup) = &problem.backup {
+            // If the backup can't be restored, keep the problem open so the
+            // player can still decide what to do with the data they have.
+            if let Err(err) = store.restore_backup(&backup.path) {
+                error!("Failed to restore backup: {err:?}");
+                self.restore_error = Some(tr!("restore-failed", error = format!("{err:#}")));
+            }
+        }
+    }
+
     /// Renders the menu bar.
-    fn render_menu_bar(&mut self, ui: &Ui) {
+    fn render_menu_bar(&mut self, ui: &Ui, core: &G::Core) {
         ui.menu_bar(|| {
-            if ui.menu_item("Settings") {
+            if ui.menu_item(tr!("menu-settings")) {
                 log::warn!("Click registered");
                 self.settings_window_visible = true;
             }
+
+            #[cfg(feature = "profile")]
+            if ui.menu_item(tr!("menu-profiler")) {
+                self.profiler_window_visible = true;
+            }
+
+            if let Some(player) = &self.log_filter
+                && ui.menu_item(tr!("log-clear-filter", player = player))
+            {
+                self.log_filter = None;
+            }
+
+            self.render_death_link_status(ui, core);
+            self.render_goal_progress(ui, core);
         });
     }
 
+    /// Renders a menu in the menu bar that lists the requirements for the
+    /// player's goal and which of them they've met.
+    fn render_goal_progress(&self, ui: &Ui, core: &G::Core) {
+        let Some(requirements) = core.goal_progress() else {
+            return;
+        };
+
+        let complete = requirements.iter().filter(|r| r.complete).count();
+        let palette = self.settings.palette();
+        ui.menu(
+            format!(
+                "{}###goal-menu",
+                tr!("goal-menu", complete = complete, total = requirements.len())
+            ),
+            || {
+                for requirement in &requirements {
+                    let (status, color) = if requirement.complete {
+                        (tr!("goal-defeated"), palette.green)
+                    } else {
+                        (tr!("goal-remaining"), palette.red)
+                    };
+                    ui.text(&requirement.name);
+                    ui.same_line();
+                    ui.text_colored(color.to_im().to_rgba_f32s(), status);
+                }
+            },
+        );
+    }
+
+    /// Renders a compact summary of the player's death link state in the menu
+    /// bar, with more details in a tooltip.
+    fn render_death_link_status(&self, ui: &Ui, core: &G::Core) {
+        let Some(status) = core.death_link_status() else {
+            return;
+        };
+
+        ui.separator();
+        if status.mode == DeathLinkMode::Off {
+            ui.text_disabled(tr!("death-link-off"));
+            return;
+        }
+
+        let mut summary = match status.mode {
+            DeathLinkMode::AnyDeath => tr!("death-link-any-death"),
+            _ => tr!("death-link-lost-souls"),
+        };
+        if status.amnesty > 1 {
+            summary.push_str(&format!(
+                " | {}",
+                tr!(
+                    "death-link-amnesty",
+                    remaining = status.remaining_amnesty(),
+                    total = status.amnesty,
+                )
+            ));
+        }
+        if let Some(grace_period) = status.grace_period {
+            summary.push_str(&format!(
+                " | {}",
+                tr!("death-link-grace", seconds = grace_period.as_secs() + 1)
+            ));
+        }
+
+        if status.grace_period.is_some() {
+            ui.text_disabled(summary);
+        } else {
+            ui.text(summary);
+        }
+
+        if ui.is_item_hovered() {
+            ui.tooltip(|| {
+                let Some(event) = &status.last_event else {
+                    ui.text(tr!("death-link-none-yet"));
+                    return;
+                };
+
+                let seconds = event.time.elapsed().as_secs();
+                ui.text(match event.direction {
+                    DeathLinkDirection::Sent => tr!("death-link-last-sent", seconds = seconds),
+                    DeathLinkDirection::Received => tr!(
+                        "death-link-last-received",
+                        player = event.source,
+                        seconds = seconds,
+                    ),
+                });
+                if let Some(cause) = &event.cause {
+                    ui.text_wrapped(cause);
+                }
+            });
+        }
+    }
+
     /// Renders the settings popup.
-    fn render_settings_window(&mut self, ui: &Ui) {
+    ///
+    /// If `reset_layout` is true, this undocks the window.
+    fn render_settings_window(&mut self, ui: &Ui, reset_layout: bool) {
         if !self.settings_window_visible {
             return;
         }
@@ -333,52 +667,291 @@ impl<G: Game> Overlay<G> {
         let settings_bg_color = [0.0, 0.0, 0.0, 1.0];
         let _bg = ui.push_style_color(StyleColor::WindowBg, settings_bg_color);
 
-        ui.window("Archipelago Overlay Settings")
+        if reset_layout {
+            // Safety: This is only ever run on the main thread during a frame.
+            unsafe { igSetNextWindowDockID(0, ImGuiCond_Always as i32) };
+        }
+
+        ui.window(format!("{}###ap-overlay-settings", tr!("settings-title")))
             .size([0., 0.], Condition::Appearing)
             .position_pivot([0.5, 0.5])
             .collapsible(false)
             .build(|| {
-                ui.text("Font Size ");
+                ui.text(format!("{} ", tr!("settings-font-size")));
                 ui.same_line();
                 if ui.button("-##font-size-decrease-button") {
-                    self.font_scale = (self.font_scale - 0.1).max(0.5);
+                    self.settings.font_scale = (self.settings.font_scale - 0.1).max(0.5);
                 }
                 ui.same_line();
                 if ui.button("+##font-size-increase-button") {
-                    self.font_scale = (self.font_scale + 0.1).min(4.0);
+                    self.settings.font_scale = (self.settings.font_scale + 0.1).min(4.0);
                 }
 
-                let mut opacity_percent = (self.unfocused_window_opacity * 100.0).round() as i32;
-                let _slider_width = ui.push_item_width(150. * self.font_scale);
-                ui.text("Unfocused Opacity ");
+                let mut opacity_percent =
+                    (self.settings.unfocused_window_opacity * 100.0).round() as i32;
+                let slider_width = ui.push_item_width(150. * self.settings.font_scale);
+                ui.text(format!("{} ", tr!("settings-unfocused-opacity")));
                 ui.same_line();
                 ui.slider_config("##unfocused-opacity-slider", 0, 100)
                     .display_format("%d%%")
                     .build(&mut opacity_percent);
-                self.unfocused_window_opacity = (opacity_percent as f32) / 100.0;
+                self.settings.unfocused_window_opacity = (opacity_percent as f32) / 100.0;
+
+                drop(slider_width);
+
+                ui.text_disabled(tr!("settings-gamepad-hint"));
+
+                self.render_locale_settings(ui);
+                self.render_theme_settings(ui);
 
-                if ui.button("Ok") {
+                if ui.button(tr!("settings-reset-layout")) {
+                    self.reset_layout_next_frame = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr!("settings-reset-layout-tooltip"));
+                }
+
+                if ui.button(tr!("settings-ok")) {
                     self.settings_window_visible = false;
+                    if let Err(e) = self.settings.save() {
+                        error!("Failed to save overlay settings: {e}");
+                    }
+                }
+            });
+    }
+
+    /// Renders the window that shows live timing data for each section of
+    /// the [crate::SectionProfiler].
+    ///
+    /// If `reset_layout` is true, this undocks the window.
+    #[cfg(feature = "profile")]
+    fn render_profiler_window(&mut self, ui: &Ui, core: &mut G::Core, reset_layout: bool) {
+        if !self.profiler_window_visible {
+            return;
+        }
+
+        if reset_layout {
+            // Safety: This is only ever run on the main thread during a frame.
+            unsafe { igSetNextWindowDockID(0, ImGuiCond_Always as i32) };
+        }
+
+        let mut save_trace = false;
+        let profiler = core.base_mut().profiler();
+        ui.window(format!("{}###ap-overlay-profiler", tr!("profiler-title")))
+            .size([500., 300.], Condition::FirstUseEver)
+            .opened(&mut self.profiler_window_visible)
+            .build(|| {
+                let paused = profiler.is_paused();
+                if ui.button(if paused {
+                    tr!("profiler-resume")
+                } else {
+                    tr!("profiler-pause")
+                }) {
+                    profiler.set_paused(!paused);
+                }
+                ui.same_line();
+                if ui.button(tr!("profiler-reset")) {
+                    profiler.reset_history();
+                }
+
+                ui.same_line();
+                let mut capturing = profiler.is_capturing();
+                if ui.checkbox(tr!("profiler-capture-trace"), &mut capturing) {
+                    profiler.set_capturing(capturing);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr!("profiler-capture-trace-tooltip"));
+                }
+                ui.same_line();
+                ui.disabled(!capturing, || {
+                    save_trace = ui.button(tr!("profiler-save-trace"));
+                });
+
+                let mut budget = profiler
+                    .budget()
+                    .map_or(0.0, |budget| budget.as_secs_f32() * 1000.0);
+                let item_width = ui.push_item_width(100. * self.settings.font_scale);
+                if ui
+                    .input_float(tr!("profiler-budget"), &mut budget)
+                    .step(0.5)
+                    .display_format("%.1f")
+                    .build()
+                {
+                    profiler.set_budget(
+                        (budget > 0.0).then(|| Duration::from_secs_f32(budget / 1000.0)),
+                    );
+                }
+                drop(item_width);
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(tr!("profiler-budget-tooltip"));
+                }
+
+                let frame = profiler.average_frame();
+                ui.same_line();
+                ui.text(tr!(
+                    "profiler-frame",
+                    milliseconds = format!("{frame:.2}"),
+                    fps = format!("{:.0}", if frame > 0.0 { 1000.0 / frame } else { 0.0 }),
+                ));
+
+                let flags = TableFlags::BORDERS_INNER_V
+                    | TableFlags::ROW_BG
+                    | TableFlags::SIZING_STRETCH_PROP
+                    | TableFlags::SCROLL_Y;
+                let Some(_table) = ui.begin_table_with_flags("##profiler-sections", 4, flags)
+                else {
+                    return;
+                };
+                ui.table_setup_scroll_freeze(0, 1);
+                ui.table_setup_column(tr!("profiler-section"));
+                ui.table_setup_column(tr!("profiler-average"));
+                ui.table_setup_column(tr!("profiler-share"));
+                ui.table_setup_column(tr!("profiler-graph"));
+                ui.table_headers_row();
+
+                let indent = ui.clone_style().indent_spacing;
+                for (name, history) in profiler.history() {
+                    let average = history.average();
+                    let frames = history.frames.iter().copied().collect::<Vec<_>>();
+
+                    ui.table_next_column();
+                    // Indent nested sections beneath the sections that contain
+                    // them.
+                    let depth = indent * history.depth as f32;
+                    if depth > 0.0 {
+                        ui.indent_by(depth);
+                    }
+                    ui.text(name);
+                    if depth > 0.0 {
+                        ui.unindent_by(depth);
+                    }
+                    if ui.is_item_hovered()
+                        && let Some(stats) = profiler.stats(name)
+                    {
+                        ui.tooltip_text(tr!(
+                            "profiler-stats",
+                            min = format!("{:.3}", stats.min.as_secs_f64() * 1000.0),
+                            p50 = format!("{:.3}", stats.p50.as_secs_f64() * 1000.0),
+                            p95 = format!("{:.3}", stats.p95.as_secs_f64() * 1000.0),
+                            p99 = format!("{:.3}", stats.p99.as_secs_f64() * 1000.0),
+                            max = format!("{:.3}", stats.max.as_secs_f64() * 1000.0),
+                        ));
+                    }
+
+                    ui.table_next_column();
+                    ui.text(format!("{average:.3} ms"));
+
+                    ui.table_next_column();
+                    let share = if frame > 0.0 { average / frame } else { 0.0 };
+                    ProgressBar::new(share.clamp(0.0, 1.0))
+                        .overlay_text(format!("{:.1}%", share * 100.0))
+                        .size([-f32::MIN_POSITIVE, 0.0])
+                        .build(ui);
+
+                    ui.table_next_column();
+                    ui.plot_lines(format!("##profiler-graph-{name}"), &frames)
+                        .scale_min(0.0)
+                        .graph_size([-f32::MIN_POSITIVE, ui.frame_height()])
+                        .build();
                 }
             });
+
+        if save_trace {
+            core.base_mut().handle_command("!trace", None);
+        }
+    }
+
+    /// Renders the part of the settings window that controls the language.
+    fn render_locale_settings(&mut self, ui: &Ui) {
+        ui.text(format!("{} ", tr!("settings-language")));
+        ui.same_line();
+
+        let _combo_width = ui.push_item_width(250. * self.settings.font_scale);
+        if let Some(_combo) = ui.begin_combo("##locale-combo", self.settings.locale.name()) {
+            for locale in Locale::ALL {
+                if ui
+                    .selectable_config(locale.name())
+                    .selected(locale == self.settings.locale)
+                    .build()
+                {
+                    self.settings.locale = locale;
+                    l10n::set_locale(locale);
+                }
+            }
+        }
+    }
+
+    /// Renders the part of the settings window that controls the color theme.
+    fn render_theme_settings(&mut self, ui: &Ui) {
+        ui.text(format!("{} ", tr!("settings-color-theme")));
+        ui.same_line();
+        {
+            let _combo_width = ui.push_item_width(250. * self.settings.font_scale);
+            if let Some(_combo) = ui.begin_combo("##theme-combo", self.settings.theme.name()) {
+                for theme in Theme::ALL {
+                    if ui
+                        .selectable_config(format!("{}##theme-{:?}", theme.name(), theme))
+                        .selected(theme == self.settings.theme)
+                        .build()
+                    {
+                        self.settings.theme = theme;
+                    }
+                }
+            }
+        }
+
+        ui.checkbox(tr!("settings-item-icons"), &mut self.settings.item_icons);
+        if ui.is_item_hovered() {
+            ui.tooltip_text(tr!("settings-item-icons-tooltip"));
+        }
+
+        if self.settings.theme != Theme::Custom {
+            return;
+        }
+
+        let flags = ColorEditFlags::NO_INPUTS | ColorEditFlags::NO_ALPHA;
+        for (i, (label, color)) in self
+            .settings
+            .custom_palette
+            .colors_mut()
+            .into_iter()
+            .enumerate()
+        {
+            if i % 4 != 0 {
+                ui.same_line();
+            }
+
+            let mut value = color.to_f32s();
+            if ui
+                .color_edit3_config(format!("{}##custom-color-{i}", tr!(label)), &mut value)
+                .flags(flags)
+                .build()
+            {
+                *color = Rgb::from_f32s(value);
+            }
+        }
+
+        if ui.button(tr!("settings-reset-colors")) {
+            self.settings.custom_palette = Palette::DEFAULT;
+        }
     }
 
     /// Renders the buttons that allow the player to reconnect to Archipelago.
     /// These take the place of the text box when the client is disconnected.
     fn render_connection_buttons(&mut self, ui: &Ui, core: &mut G::Core) {
-        if ui.button("Reconnect") {
+        if ui.button(tr!("reconnect")) {
             core.base_mut().reconnect();
         }
 
         ui.same_line();
-        if ui.button("Change URL") {
+        if ui.button(tr!("change-url")) {
             ui.open_popup("#url-modal-popup");
             core.base().config().url().clone_into(&mut self.popup_url);
         }
     }
 
     /// Renders the log window which displays all the prints sent from the server.
-    fn render_log_window(&mut self, ui: &Ui, core: &G::Core) {
+    fn render_log_window(&mut self, ui: &Ui, core: &mut G::Core) {
         let style = ui.clone_style();
 
         let scrollbar_bg_opacity = if self.was_window_focused { 1.0 } else { 0.0 };
@@ -390,64 +963,74 @@ impl<G: Game> Overlay<G> {
             style.window_padding[1],
         ]));
 
-        let is_compact_mode = self.is_compact_mode(core);
-        let input_height = if !is_compact_mode {
-            ui.frame_height_with_spacing()
-        } else {
-            0.0
-        };
-
         ui.child_window("#log")
-            .size([0.0, -input_height.ceil()])
+            .size([0.0, 0.0])
             .draw_background(false)
             .always_vertical_scrollbar(true)
-            .always_horizontal_scrollbar(!is_compact_mode)
             .build(|| {
                 if let Some((_, log_time)) = core.base().logs().last()
                     && log_time > &self.last_log_emitted
                 {
+                    if let Some(fonts) = self.fonts.as_mut() {
+                        for (message, _) in core
+                            .base()
+                            .logs()
+                            .rev()
+                            .take_while(|(_, time)| time > &self.last_log_emitted)
+                        {
+                            fonts.request_glyphs(&message.to_string());
+                        }
+                    }
+
                     self.frames_since_new_logs = 0;
                     self.last_log_emitted = *log_time;
                 }
 
-                let clipper = ListClipper::new(core.base().logs().len().try_into().unwrap());
-                let mut clip = clipper.begin(ui);
-                while clip.step() {
-                    for (message, _) in core
-                        .base()
-                        .logs()
-                        .skip(clip.display_start().try_into().unwrap_or(0))
-                        .take(
-                            (clip.display_end() - clip.display_start())
-                                .try_into()
-                                .unwrap_or(0),
-                        )
-                    {
+                let slot = core.base().config().slot();
+                self.log_layout.update(
+                    ui,
+                    self.fonts.as_ref(),
+                    self.settings.palette(),
+                    self.settings.item_icons,
+                    self.log_filter.as_deref(),
+                    core.base().logs().map(|(message, _)| message),
+                    core.base().log_count(),
+                    |message| {
                         use ap::Print::*;
-                        write_message_data(
-                            ui,
-                            message.data(),
-                            // De-emphasize miscellaneous server prints.
-                            match message {
-                                Chat { .. }
-                                | ServerChat { .. }
-                                | Tutorial { .. }
-                                | CommandResult { .. }
-                                | AdminCommandResult { .. }
-                                | Unknown { .. } => 0xff,
-                                ItemSend { item, .. }
-                                | ItemCheat { item, .. }
-                                | Hint { item, .. }
-                                    if core.base().config().slot() == item.receiver().name()
-                                        || core.base().config().slot() == item.sender().name() =>
-                                {
-                                    0xFF
-                                }
-                                _ => 0xAA,
-                            },
-                        );
+                        // De-emphasize miscellaneous server prints.
+                        match message {
+                            Chat { .. }
+                            | ServerChat { .. }
+                            | Tutorial { .. }
+                            | CommandResult { .. }
+                            | AdminCommandResult { .. }
+                            | Unknown { .. } => 0xff,
+                            ItemSend { item, .. } | ItemCheat { item, .. } | Hint { item, .. }
+                                if slot == item.receiver().name()
+                                    || slot == item.sender().name() =>
+                            {
+                                0xFF
+                            }
+                            _ => 0xAA,
+                        }
+                    },
+                );
+                match self.log_layout.render(ui, self.fonts.as_ref(), slot) {
+                    Some(LinkAction::Hint(item)) if core.client().is_some() => {
+                        self.say(format!("!hint {item}"), core)
                     }
+                    Some(LinkAction::FilterByPlayer(player)) => self.log_filter = Some(player),
+                    _ => {}
                 }
+
+                // Let controller players scroll the log with the right stick
+                // without having to navigate into it first.
+                let scroll = self.gamepad.scroll();
+                if scroll != 0.0 {
+                    let speed = ui.text_line_height_with_spacing() * 0.5;
+                    ui.set_scroll_y((ui.scroll_y() - scroll * speed).clamp(0.0, ui.scroll_max_y()));
+                }
+
                 if self.log_was_scrolled_down && self.frames_since_new_logs < 10 {
                     ui.set_scroll_y(ui.scroll_max_y());
                 }
@@ -462,7 +1045,7 @@ impl<G: Game> Overlay<G> {
         ui.disabled(core.client().is_none(), || {
             let arrow_button_width = ui.frame_height(); // Arrow buttons are square buttons.
             let style = ui.clone_style();
-            let spacing = style.item_spacing[0] * self.font_scale * 0.7;
+            let spacing = style.item_spacing[0] * self.settings.font_scale * 0.7;
 
             let input_width = ui.push_item_width(-(arrow_button_width + spacing));
             if focus {
@@ -474,6 +1057,9 @@ impl<G: Game> Overlay<G> {
                 .callback(InputTextCallback::HISTORY, &mut self.say_history)
                 .build();
             drop(input_width);
+            if let Some(fonts) = self.fonts.as_mut() {
+                fonts.request_glyphs(&self.say_input);
+            }
 
             ui.same_line_with_spacing(0.0, spacing);
             send = ui.arrow_button("##say-button", Direction::Right) || send;
@@ -499,13 +1085,13 @@ impl<G: Game> Overlay<G> {
 
         let command = captures.get(1).unwrap().as_str();
         let arg = captures.get(3).map(|c| c.as_str());
-        if !core.handle_command(command, arg) {
+        if !core.handle_command(command, arg) && !core.handle_builtin_command(command, arg) {
             let _ = core.client_mut().unwrap().say(message);
         }
     }
 
     /// Returns whether the overlay is currently in "compact mode", where the
-    /// bottommost widgets are not rendered.
+    /// input window is not rendered.
     fn is_compact_mode(&self, core: &G::Core) -> bool {
         // When the connection is inactive, always show the buttons to
         // reconnect.
@@ -524,30 +1110,86 @@ impl ImColor32Ext for ImColor32 {
     }
 }
 
-/// Writes the text in [parts] to [ui] in a single line.
-fn write_message_data(ui: &Ui, parts: &[RichText], alpha: u8) {
-    let mut first = true;
-    for part in parts {
-        if !first {
-            ui.same_line();
-        }
-        first = false;
+/// The visual style with which to render a single [RichText] part.
+#[derive(Clone, Copy, PartialEq)]
+struct PartStyle {
+    /// The color of the text itself.
+    color: ImColor32,
+
+    /// The color to draw behind the text, if any.
+    background: Option<ImColor32>,
 
-        // TODO: Load in fonts to support bold, maybe write a line manually for
-        // underline? I'm not sure there's a reasonable way to support
-        // background colors.
+    /// Whether to render the text in bold.
+    bold: bool,
+
+    /// Whether to draw a line underneath the text.
+    underline: bool,
+}
+
+impl PartStyle {
+    /// Returns the style to use for [part] with the colors in [palette].
+    fn for_part(part: &RichText, palette: &Palette) -> Self {
         use RichText::*;
+        match part {
+            Player { .. } | PlayerName { .. } => Self::color(palette.player),
+            Item { .. } => {
+                Self::color(ItemClassification::of(part).map_or(palette.item, |c| c.color(palette)))
+            }
+            Location { .. } | EntranceName { .. } => Self::color(palette.location),
+            Color { color, .. } => Self::for_text_color(color, palette),
+            _ => Self::color(palette.white),
+        }
+    }
+
+    /// Returns the style to use for an explicit [TextColor] with the colors in
+    /// [palette].
+    fn for_text_color(color: &TextColor, palette: &Palette) -> Self {
         use TextColor::*;
-        let color = match part {
-            Player { .. } | PlayerName { .. } | Color { color: Blue, .. } => BLUE,
-            Item { .. } | Color { color: Magenta, .. } => MAGENTA,
-            Location { .. } | EntranceName { .. } | Color { color: Cyan, .. } => CYAN,
-            Color { color: Black, .. } => BLACK,
-            Color { color: Red, .. } => RED,
-            Color { color: Green, .. } => GREEN,
-            Color { color: Yellow, .. } => YELLOW,
-            _ => WHITE,
-        };
-        ui.text_colored(color.with_alpha(alpha).to_rgba_f32s(), part.to_string());
+        match color {
+            Bold => Self {
+                bold: true,
+                ..Self::color(palette.white)
+            },
+            Underline => Self {
+                underline: true,
+                ..Self::color(palette.white)
+            },
+            Black => Self::color(palette.black),
+            Red => Self::color(palette.red),
+            Green => Self::color(palette.green),
+            Yellow => Self::color(palette.yellow),
+            Blue => Self::color(palette.blue),
+            Magenta => Self::color(palette.magenta),
+            Cyan => Self::color(palette.cyan),
+            White => Self::color(palette.white),
+            BlackBg => Self::background(palette.black),
+            RedBg => Self::background(palette.red),
+            GreenBg => Self::background(palette.green),
+            YellowBg => Self::background(palette.yellow),
+            BlueBg => Self::background(palette.blue),
+            MagentaBg => Self::background(palette.magenta),
+            CyanBg => Self::background(palette.cyan),
+            WhiteBg => Self::background(palette.white),
+        }
+    }
+
+    /// Returns a style with the given text color and no other decoration.
+    fn color(color: Rgb) -> Self {
+        Self {
+            color: color.to_im(),
+            background: None,
+            bold: false,
+            underline: false,
+        }
+    }
+
+    /// Returns a style with the given background color and legible text on top
+    /// of it.
+    fn background(background: Rgb) -> Self {
+        Self {
+            background: Some(background.to_im()),
+            color: BACKGROUND_TEXT,
+            ..Self::color(background)
+        }
     }
 }
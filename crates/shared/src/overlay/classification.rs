@@ -0,0 +1,78 @@
+use archipelago_rs::{self as ap, RichText};
+
+use super::theme::{Palette, Rgb};
+use crate::tr;
+
+/// The importance of an Archipelago item, as indicated by its flags. This
+/// follows the conventions of the standard Archipelago text client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemClassification {
+    /// The item may be required to progress through some game.
+    Progression,
+
+    /// The item is generally helpful, but never required.
+    Useful,
+
+    /// The item is a trap that will do something unpleasant to its receiver.
+    Trap,
+
+    /// The item is none of the above.
+    Filler,
+}
+
+impl ItemClassification {
+    /// Returns the classification for the given item flags. If an item has
+    /// multiple flags, the most important one wins.
+    pub fn from_flags(flags: ap::ItemFlags) -> Self {
+        if flags.contains(ap::ItemFlags::PROGRESSION) {
+            ItemClassification::Progression
+        } else if flags.contains(ap::ItemFlags::USEFUL) {
+            ItemClassification::Useful
+        } else if flags.contains(ap::ItemFlags::TRAP) {
+            ItemClassification::Trap
+        } else {
+            ItemClassification::Filler
+        }
+    }
+
+    /// Returns the classification of the item in [part], or None if it isn't
+    /// an item.
+    pub fn of(part: &RichText) -> Option<Self> {
+        match part {
+            RichText::Item { flags, .. } => Some(Self::from_flags(*flags)),
+            _ => None,
+        }
+    }
+
+    /// Returns the human-readable name of this classification in the current
+    /// locale.
+    pub fn name(&self) -> String {
+        match self {
+            ItemClassification::Progression => tr!("item-class-progression"),
+            ItemClassification::Useful => tr!("item-class-useful"),
+            ItemClassification::Trap => tr!("item-class-trap"),
+            ItemClassification::Filler => tr!("item-class-filler"),
+        }
+    }
+
+    /// Returns the color for items with this classification in [palette].
+    pub fn color(&self, palette: &Palette) -> Rgb {
+        match self {
+            ItemClassification::Progression => palette.progression_item,
+            ItemClassification::Useful => palette.useful_item,
+            ItemClassification::Trap => palette.trap_item,
+            ItemClassification::Filler => palette.item,
+        }
+    }
+
+    /// Returns the icon to display before items with this classification, if
+    /// any. These are all covered by the overlay's bundled font.
+    pub fn icon(&self) -> Option<&'static str> {
+        match self {
+            ItemClassification::Progression => Some("★"),
+            ItemClassification::Useful => Some("◆"),
+            ItemClassification::Trap => Some("⚠"),
+            ItemClassification::Filler => None,
+        }
+    }
+}
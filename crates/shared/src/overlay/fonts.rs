@@ -0,0 +1,315 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::{env, fs, thread, time::Duration, time::Instant};
+
+use hudhook::RenderContext;
+use imgui::*;
+use log::*;
+
+use crate::utils;
+
+/// The regular-weight font used for most overlay text.
+const REGULAR_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+
+/// The bold font used for [archipelago_rs::TextColor::Bold] text.
+const BOLD_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans-Bold.ttf");
+
+/// The size at which fonts are rasterized. This matches imgui's default font so
+/// that the overlay's font scale means the same thing it always has.
+const FONT_SIZE: f32 = 13.0;
+
+/// The glyph ranges that are always loaded from the bundled fonts. This covers
+/// Latin, Greek, Cyrillic, and common symbols, which DejaVu Sans supports well
+/// and which are cheap enough to rasterize up front.
+const BASE_GLYPH_RANGES: &[u32] = &[
+    0x0020, 0x052F, // Latin, Greek, Cyrillic
+    0x1E00, 0x1FFF, // Latin Extended Additional, Greek Extended
+    0x2000, 0x206F, // General Punctuation
+    0x20A0, 0x20CF, // Currency Symbols
+    0x2100, 0x21FF, // Letterlike Symbols, Arrows
+    0x2500, 0x25FF, // Box Drawing, Geometric Shapes
+    0x2600, 0x26FF, // Miscellaneous Symbols
+    0,
+];
+
+/// The basenames of fonts in the Windows font directory that are used to fill
+/// in glyphs the bundled fonts don't cover, in order of preference. Each glyph
+/// is taken from the first font that contains it.
+const SYSTEM_FALLBACK_FONTS: &[&str] = &[
+    "msgothic.ttc", // Japanese
+    "msyh.ttc",     // Simplified Chinese
+    "msjh.ttc",     // Traditional Chinese
+    "malgun.ttf",   // Korean
+    "seguisym.ttf", // Miscellaneous symbols
+    "seguiemj.ttf", // Emoji (rendered in monochrome)
+];
+
+/// The maximum number of glyphs outside [BASE_GLYPH_RANGES] that will be added
+/// to the atlas. This keeps the atlas texture from growing without bound in
+/// rooms with a lot of CJK chat.
+const MAX_FALLBACK_GLYPHS: usize = 4096;
+
+/// The minimum amount of time between atlas rebuilds. New glyphs that show up
+/// in the meantime are batched into the next rebuild.
+const REBUILD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The fonts loaded into the imgui context for the overlay.
+pub struct Fonts {
+    /// The default font. This is the first font added to the atlas, so imgui
+    /// uses it for everything that doesn't explicitly push another font.
+    pub regular: FontId,
+
+    /// The bold variant of [regular]. This only covers [BASE_GLYPH_RANGES];
+    /// other glyphs fall back to imgui's replacement character.
+    pub bold: FontId,
+
+    /// Every glyph outside [BASE_GLYPH_RANGES] that's been requested so far.
+    fallback_glyphs: BTreeSet<u32>,
+
+    /// Whether [fallback_glyphs] has changed since the atlas was last built.
+    dirty: bool,
+
+    /// The time the atlas was last rebuilt.
+    last_rebuild: Instant,
+
+    /// The number of times the atlas has been rebuilt. Text measurements taken
+    /// with an older generation may no longer be accurate.
+    generation: u32,
+
+    /// The zero-terminated imgui glyph ranges for [fallback_glyphs]. imgui
+    /// holds a pointer to this while it builds the atlas, so it must not be
+    /// modified in the meantime.
+    fallback_ranges: Vec<u32>,
+
+    /// The fallback font data being read on a background thread for the next
+    /// rebuild, if any. The data is dropped once the atlas is built so that
+    /// CJK fonts don't stay in memory between rebuilds.
+    loading: Option<Receiver<Vec<Vec<u8>>>>,
+}
+
+impl Fonts {
+    /// Adds the overlay's fonts to `ctx`'s font atlas. This must be called
+    /// before the renderer builds the atlas texture, which in practice means
+    /// during [hudhook::ImguiRenderLoop::initialize].
+    ///
+    /// Fallback fonts aren't loaded here. They're added lazily by
+    /// [Self::rebuild_if_needed] once text actually needs them.
+    pub fn load(ctx: &mut Context) -> Self {
+        let atlas = ctx.fonts();
+        Self {
+            regular: atlas.add_font(&[font_source(REGULAR_FONT)]),
+            bold: atlas.add_font(&[font_source(BOLD_FONT)]),
+            fallback_glyphs: Default::default(),
+            dirty: false,
+            last_rebuild: Instant::now(),
+            generation: 0,
+            fallback_ranges: vec![],
+            loading: None,
+        }
+    }
+
+    /// Returns the number of times the atlas has been rebuilt.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Records that `text` is going to be displayed, so that any glyphs it
+    /// contains which aren't yet in the atlas are added on the next
+    /// [Self::rebuild_if_needed].
+    pub fn request_glyphs(&mut self, text: &str) {
+        for c in text.chars() {
+            let c = u32::from(c);
+            if self.fallback_glyphs.len() >= MAX_FALLBACK_GLYPHS {
+                return;
+            } else if c >= 0x20 && !is_base_glyph(c) && self.fallback_glyphs.insert(c) {
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Rebuilds the font atlas and uploads it to the GPU if new glyphs have
+    /// been requested since the last build.
+    ///
+    /// This must be called outside a frame, before [Context::new_frame].
+    pub fn rebuild_if_needed(&mut self, ctx: &mut Context, render_context: &mut dyn RenderContext) {
+        if !self.dirty || self.last_rebuild.elapsed() < REBUILD_INTERVAL {
+            return;
+        }
+        // Leave [dirty] set until the fonts are loaded so we try again on a
+        // later frame.
+        let Some(fallback_data) = self.fallback_fonts() else {
+            return;
+        };
+        self.dirty = false;
+        self.last_rebuild = Instant::now();
+
+        if fallback_data.is_empty() {
+            // There's nothing we can do to render these glyphs, so don't
+            // bother rebuilding.
+            return;
+        }
+        info!(
+            "Rebuilding font atlas with {} fallback glyphs",
+            self.fallback_glyphs.len()
+        );
+
+        self.fallback_ranges = glyph_ranges(&self.fallback_glyphs);
+        // Safety: [fallback_ranges] isn't modified again until the next
+        // rebuild, and we clear imgui's reference to it once the atlas is
+        // built below.
+        let ranges = FontGlyphRanges::from_slice(unsafe {
+            &*(self.fallback_ranges.as_slice() as *const [u32])
+        });
+
+        let atlas = ctx.fonts();
+        let tex_id = atlas.tex_id;
+        atlas.clear();
+
+        let mut regular = vec![font_source(REGULAR_FONT)];
+        regular.extend(fallback_data.iter().map(|data| FontSource::TtfData {
+            data,
+            size_pixels: FONT_SIZE,
+            config: Some(FontConfig {
+                glyph_ranges: ranges.clone(),
+                // CJK fonts have so many glyphs that oversampling them would
+                // balloon the atlas, and their strokes are simple enough that
+                // it doesn't help much anyway.
+                oversample_h: 1,
+                oversample_v: 1,
+                ..Default::default()
+            }),
+        }));
+        self.regular = atlas.add_font(&regular);
+        self.bold = atlas.add_font(&[font_source(BOLD_FONT)]);
+        self.generation += 1;
+
+        let texture = atlas.build_rgba32_texture();
+        let result = render_context
+            .replace_texture(tex_id, texture.data, texture.width, texture.height)
+            .map(|_| tex_id)
+            .or_else(|_| render_context.load_texture(texture.data, texture.width, texture.height));
+        match result {
+            Ok(id) => atlas.tex_id = id,
+            Err(err) => warn!("Failed to upload rebuilt font atlas: {err:?}"),
+        }
+
+        // The atlas has its own copy of everything it needs now. Drop imgui's
+        // copy of the font data, which can be tens of megabytes for CJK fonts.
+        // Our own copy is dropped when we return, and read again for the next
+        // rebuild.
+        atlas.clear_input_data();
+    }
+
+    /// Returns the data for all available fallback fonts, or None if they're
+    /// still being loaded. The first call after a rebuild starts reading them
+    /// on a background thread so that reading tens of megabytes of CJK fonts
+    /// doesn't stall the render thread.
+    fn fallback_fonts(&mut self) -> Option<Vec<Vec<u8>>> {
+        let loading = self.loading.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let fonts = load_fallback_fonts();
+                debug!("Loaded {} fallback fonts", fonts.len());
+                let _ = tx.send(fonts);
+            });
+            rx
+        });
+
+        match loading.try_recv() {
+            Ok(fonts) => {
+                self.loading = None;
+                Some(fonts)
+            }
+            Err(TryRecvError::Empty) => None,
+            // The loader panicked. Try again on the next frame.
+            Err(TryRecvError::Disconnected) => {
+                self.loading = None;
+                None
+            }
+        }
+    }
+}
+
+/// Returns whether `c` is in [BASE_GLYPH_RANGES].
+fn is_base_glyph(c: u32) -> bool {
+    BASE_GLYPH_RANGES
+        .chunks_exact(2)
+        .any(|range| (range[0]..=range[1]).contains(&c))
+}
+
+/// Converts a set of codepoints to a zero-terminated list of inclusive imgui
+/// glyph ranges.
+fn glyph_ranges(glyphs: &BTreeSet<u32>) -> Vec<u32> {
+    let mut ranges = Vec::<u32>::new();
+    for &c in glyphs {
+        match ranges.last_mut() {
+            Some(end) if *end + 1 == c => *end = c,
+            _ => ranges.extend([c, c]),
+        }
+    }
+    ranges.push(0);
+    ranges
+}
+
+/// Loads the data for all available fallback fonts.
+///
+/// Fonts in the `fonts` directory next to the mod take precedence, so that
+/// users (particularly on Linux, where Windows system fonts may be missing)
+/// can supply their own. Then we use whatever [SYSTEM_FALLBACK_FONTS] exist.
+fn load_fallback_fonts() -> Vec<Vec<u8>> {
+    let mut paths = Vec::<PathBuf>::new();
+    if let Ok(dir) = utils::mod_directory()
+        && let Ok(entries) = fs::read_dir(dir.join("fonts"))
+    {
+        let mut user_fonts = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| matches!(e.to_lowercase().as_str(), "ttf" | "ttc" | "otf"))
+            })
+            .collect::<Vec<_>>();
+        user_fonts.sort();
+        paths.extend(user_fonts);
+    }
+
+    let windows_dir = env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+    let system_fonts = PathBuf::from(windows_dir).join("Fonts");
+    paths.extend(SYSTEM_FALLBACK_FONTS.iter().map(|f| system_fonts.join(f)));
+
+    paths
+        .into_iter()
+        .filter_map(|path| match fs::read(&path) {
+            Ok(data) => Some(data),
+            Err(err) => {
+                debug!("Couldn't load fallback font {path:?}: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns a [FontSource] for the given TTF data with the overlay's standard
+/// configuration.
+fn font_source(data: &'static [u8]) -> FontSource<'static> {
+    FontSource::TtfData {
+        data,
+        size_pixels: FONT_SIZE,
+        config: Some(FontConfig {
+            glyph_ranges: FontGlyphRanges::from_slice(BASE_GLYPH_RANGES),
+            ..font_config()
+        }),
+    }
+}
+
+/// Returns the overlay's standard font configuration.
+fn font_config() -> FontConfig {
+    FontConfig {
+        // The overlay is almost always scaled up, so oversample to keep the
+        // glyphs from getting too blurry.
+        oversample_h: 3,
+        oversample_v: 2,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use imgui::{Io, Key};
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::UI::Input::XboxController::*;
+
+/// The buttons the player holds to give the overlay control of the gamepad, or
+/// to give it back to the game.
+const FOCUS_CHORD: u16 = XINPUT_GAMEPAD_BACK.0 | XINPUT_GAMEPAD_RIGHT_THUMB.0;
+
+/// The XInput buttons that map directly onto imgui gamepad keys.
+const BUTTONS: [(XINPUT_GAMEPAD_BUTTON_FLAGS, Key); 14] = [
+    (XINPUT_GAMEPAD_A, Key::GamepadFaceDown),
+    (XINPUT_GAMEPAD_B, Key::GamepadFaceRight),
+    (XINPUT_GAMEPAD_X, Key::GamepadFaceLeft),
+    (XINPUT_GAMEPAD_Y, Key::GamepadFaceUp),
+    (XINPUT_GAMEPAD_DPAD_UP, Key::GamepadDpadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, Key::GamepadDpadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, Key::GamepadDpadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, Key::GamepadDpadRight),
+    (XINPUT_GAMEPAD_START, Key::GamepadStart),
+    (XINPUT_GAMEPAD_BACK, Key::GamepadBack),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, Key::GamepadL1),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, Key::GamepadR1),
+    (XINPUT_GAMEPAD_LEFT_THUMB, Key::GamepadL3),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, Key::GamepadR3),
+];
+
+/// The distance a thumb stick has to move from the center before it counts as
+/// being pressed in that direction. This is XInput's recommended dead zone for
+/// the left stick, which is slightly more generous than the right.
+const STICK_DEADZONE: i16 = 7849;
+
+/// The amount a trigger has to be pressed before it counts as being held. This
+/// is XInput's recommended threshold.
+const TRIGGER_THRESHOLD: u8 = 30;
+
+/// How often to look for a controller when none is connected. XInput can stall
+/// for a noticeable amount of time when queried for a disconnected controller,
+/// so we avoid doing this every frame.
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reads the state of the player's XInput controller and forwards it to imgui
+/// while the overlay has control of the gamepad.
+///
+/// imgui's gamepad navigation is only fed input while [active] is true. The
+/// rest of the time, the game receives the controller's input as usual.
+#[derive(Default)]
+pub struct Gamepad {
+    /// The XInput user index of the controller we're reading, or None if we
+    /// haven't found a connected controller.
+    user_index: Option<u32>,
+
+    /// The last time we looked for a connected controller.
+    last_scan: Option<Instant>,
+
+    /// The raw state of the controller as of the last [poll].
+    state: XINPUT_GAMEPAD,
+
+    /// The buttons that were held as of the [poll] before the last.
+    previous_buttons: u16,
+
+    /// Whether the overlay currently has control of the gamepad.
+    active: bool,
+}
+
+impl Gamepad {
+    /// Reads the current state of the controller. This should be called once
+    /// per frame.
+    pub fn poll(&mut self) {
+        self.previous_buttons = self.state.wButtons.0;
+
+        if self.user_index.is_none()
+            && self
+                .last_scan
+                .is_none_or(|time| time.elapsed() >= SCAN_INTERVAL)
+        {
+            self.last_scan = Some(Instant::now());
+            self.user_index = (0..XUSER_MAX_COUNT).find(|&i| get_state(i).is_some());
+        }
+
+        match self.user_index.and_then(get_state) {
+            Some(state) => self.state = state,
+            None => {
+                self.user_index = None;
+                self.state = Default::default();
+            }
+        }
+    }
+
+    /// Returns whether the player pressed [FOCUS_CHORD] as of the last [poll].
+    pub fn chord_pressed(&self) -> bool {
+        self.state.wButtons.0 & FOCUS_CHORD == FOCUS_CHORD
+            && self.previous_buttons & FOCUS_CHORD != FOCUS_CHORD
+    }
+
+    /// Returns whether the overlay currently has control of the gamepad.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Sets whether the overlay has control of the gamepad.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Reports the controller's state to imgui. While the overlay doesn't have
+    /// control of the gamepad, this reports every key as released so that
+    /// imgui never sees input that was meant for the game.
+    pub fn feed(&self, io: &mut Io) {
+        let state = if self.active {
+            self.state
+        } else {
+            Default::default()
+        };
+
+        for (button, key) in BUTTONS {
+            io.add_key_event(key, state.wButtons.0 & button.0 != 0);
+        }
+
+        io.add_key_event(Key::GamepadL2, state.bLeftTrigger > TRIGGER_THRESHOLD);
+        io.add_key_event(Key::GamepadR2, state.bRightTrigger > TRIGGER_THRESHOLD);
+
+        let sticks = [
+            (
+                state.sThumbLX,
+                Key::GamepadLStickLeft,
+                Key::GamepadLStickRight,
+            ),
+            (state.sThumbLY, Key::GamepadLStickDown, Key::GamepadLStickUp),
+            (
+                state.sThumbRX,
+                Key::GamepadRStickLeft,
+                Key::GamepadRStickRight,
+            ),
+            (state.sThumbRY, Key::GamepadRStickDown, Key::GamepadRStickUp),
+        ];
+        for (value, negative, positive) in sticks {
+            io.add_key_event(negative, value < -STICK_DEADZONE);
+            io.add_key_event(positive, value > STICK_DEADZONE);
+        }
+    }
+
+    /// Returns how far the right stick is pushed vertically, from -1.0 (all the
+    /// way down) to 1.0 (all the way up), or 0 if the overlay doesn't have
+    /// control of the gamepad. This is used to scroll the log.
+    pub fn scroll(&self) -> f32 {
+        let value = self.state.sThumbRY;
+        if !self.active || value.unsigned_abs() <= STICK_DEADZONE.unsigned_abs() {
+            0.0
+        } else {
+            f32::from(value) / f32::from(i16::MAX)
+        }
+    }
+}
+
+/// Returns the state of the controller with the given XInput user index, or
+/// None if it isn't connected.
+fn get_state(user_index: u32) -> Option<XINPUT_GAMEPAD> {
+    let mut state = XINPUT_STATE::default();
+    // Safety: `state` is a valid, writable XINPUT_STATE.
+    let result = unsafe { XInputGetState(user_index, &mut state) };
+    (result == ERROR_SUCCESS.0).then_some(state.Gamepad)
+}
@@ -0,0 +1,119 @@
+use archipelago_rs::RichText;
+use imgui::Ui;
+
+use super::ItemClassification;
+use crate::tr;
+
+/// The kind of thing a [Link] refers to, along with any extra information we
+/// know about it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkKind {
+    /// A player in the multiworld.
+    Player {
+        /// The game the player is playing, if known.
+        game: Option<String>,
+    },
+
+    /// An item in the multiworld.
+    Item {
+        /// The item's classification.
+        classification: ItemClassification,
+
+        /// The name of the player whose world the item belongs to.
+        owner: String,
+    },
+
+    /// A location in the multiworld.
+    Location {
+        /// The name of the player whose world the location is in.
+        owner: String,
+    },
+}
+
+/// A part of a log message that the player can hover for more information or
+/// right-click for contextual actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// The name of the player, item, or location.
+    pub name: String,
+
+    /// What kind of thing this refers to.
+    pub kind: LinkKind,
+}
+
+/// An action the player chose from a [Link]'s context menu.
+pub enum LinkAction {
+    /// Ask the server for a hint for the named item.
+    Hint(String),
+
+    /// Only show log messages that mention the named player.
+    FilterByPlayer(String),
+}
+
+impl Link {
+    /// Returns the link for [part], or None if it's not something the player
+    /// can interact with.
+    pub fn for_part(part: &RichText) -> Option<Self> {
+        let kind = match part {
+            RichText::Player { player, .. } => LinkKind::Player {
+                game: Some(player.game().to_string()),
+            },
+            RichText::PlayerName { .. } => LinkKind::Player { game: None },
+            RichText::Item { player, flags, .. } => LinkKind::Item {
+                classification: ItemClassification::from_flags(*flags),
+                owner: player.name().to_string(),
+            },
+            RichText::Location { player, .. } => LinkKind::Location {
+                owner: player.name().to_string(),
+            },
+            _ => return None,
+        };
+
+        Some(Link {
+            name: part.to_string(),
+            kind,
+        })
+    }
+
+    /// Renders a tooltip with extra information about this link.
+    pub fn render_tooltip(&self, ui: &Ui) {
+        ui.tooltip(|| {
+            ui.text(&self.name);
+            match &self.kind {
+                LinkKind::Player { game: Some(game) } => {
+                    ui.text_disabled(tr!("link-player-game", game = game))
+                }
+                LinkKind::Player { game: None } => {}
+                LinkKind::Item {
+                    classification,
+                    owner,
+                } => {
+                    ui.text_disabled(classification.name());
+                    ui.text_disabled(tr!("link-item-owner", owner = owner));
+                }
+                LinkKind::Location { owner } => {
+                    ui.text_disabled(tr!("link-location-owner", owner = owner))
+                }
+            }
+        });
+    }
+
+    /// Renders the contents of this link's context menu, and returns the action
+    /// the player chose, if any. `slot` is the name of the local player, who
+    /// can only hint for items in their own world.
+    pub fn render_context_menu(&self, ui: &Ui, slot: &str) -> Option<LinkAction> {
+        let mut action = None;
+        if matches!(&self.kind, LinkKind::Item { owner, .. } if owner == slot)
+            && ui.menu_item(tr!("link-hint"))
+        {
+            action = Some(LinkAction::Hint(self.name.clone()));
+        }
+        if ui.menu_item(tr!("link-copy")) {
+            ui.set_clipboard_text(&self.name);
+        }
+        if matches!(self.kind, LinkKind::Player { .. }) && ui.menu_item(tr!("link-filter")) {
+            action = Some(LinkAction::FilterByPlayer(self.name.clone()));
+        }
+        action
+    }
+}
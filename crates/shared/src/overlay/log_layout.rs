@@ -0,0 +1,409 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use archipelago_rs::{Print, RichText};
+use imgui::*;
+
+use super::link::{Link, LinkAction};
+use super::{Fonts, ImColor32Ext, ItemClassification, Palette, PartStyle};
+
+/// The ID of the context menu popup for links in the log.
+const LINK_POPUP_ID: &str = "##log-link-menu";
+
+/// A run of text within a single wrapped line that's all rendered with the
+/// same style.
+struct Fragment {
+    /// The text to render.
+    text: String,
+
+    /// The style with which to render [text].
+    style: PartStyle,
+
+    /// The player, item, or location this fragment refers to, if any.
+    link: Option<Arc<Link>>,
+}
+
+/// The wrapped layout of a single log message.
+struct MessageLayout {
+    /// The opacity with which to render this message.
+    alpha: u8,
+
+    /// The index of this message's first line, counting every line laid out
+    /// since the layout was last reset (including lines that have since been
+    /// dropped). This lets [LogLayout::lines_from] find the first visible line
+    /// without walking the whole log.
+    first_line: usize,
+
+    /// The visual lines this message has been wrapped into. This contains at
+    /// least one (possibly empty) line unless the message is filtered out of
+    /// the log, in which case it's empty.
+    lines: Vec<Vec<Fragment>>,
+}
+
+/// The parameters that affect how text is wrapped. If any of these change, the
+/// entire log must be laid out again.
+#[derive(PartialEq)]
+struct LayoutKey {
+    /// The width available to the log.
+    width: f32,
+
+    /// The global font scale.
+    font_scale: f32,
+
+    /// The [Fonts::generation] the layout was measured with.
+    font_generation: Option<u32>,
+
+    /// The palette the layout's colors were chosen from.
+    palette: Palette,
+
+    /// Whether item names are prefixed with their classification icons.
+    item_icons: bool,
+
+    /// The player whose messages the log is filtered to, if any.
+    filter: Option<String>,
+}
+
+/// A cache of the log messages wrapped to fit the width of the log window.
+///
+/// imgui's [ListClipper] requires every item to have the same height, so rather
+/// than clipping messages (which may span several lines), we clip the visual
+/// lines they've been wrapped into.
+#[derive(Default)]
+pub struct LogLayout {
+    /// The parameters the current layout was computed with, or None if nothing
+    /// has been laid out yet.
+    key: Option<LayoutKey>,
+
+    /// The layouts for each message in the log buffer, in the same order.
+    messages: VecDeque<MessageLayout>,
+
+    /// The total number of lines across all [messages].
+    line_count: usize,
+
+    /// The number of lines that have been dropped from the front of
+    /// [messages] since the layout was last reset. This is the
+    /// [MessageLayout::first_line] of the first message.
+    dropped_lines: usize,
+
+    /// The value of [crate::CoreBase::log_count] when the log was last laid
+    /// out.
+    log_count: usize,
+
+    /// The link whose context menu is open, if any.
+    context_link: Option<Arc<Link>>,
+}
+
+impl LogLayout {
+    /// Brings the layout up to date with `logs`, which should contain every
+    /// message in the log buffer. `log_count` is the total number of logs
+    /// emitted in this session, which is used to determine how many of `logs`
+    /// are new.
+    ///
+    /// The current window's remaining content width is used as the wrapping
+    /// width, colors are chosen from `palette`, items are prefixed with icons
+    /// if `item_icons` is true, only messages that mention the player named
+    /// `filter` are included if it's set, and `alpha` is called to determine
+    /// the opacity of each message.
+    pub fn update<'a>(
+        &mut self,
+        ui: &Ui,
+        fonts: Option<&Fonts>,
+        palette: &Palette,
+        item_icons: bool,
+        filter: Option<&str>,
+        logs: impl ExactSizeIterator<Item = &'a Print>,
+        log_count: usize,
+        alpha: impl Fn(&Print) -> u8,
+    ) {
+        let key = LayoutKey {
+            width: ui.content_region_avail()[0],
+            font_scale: ui.io().font_global_scale,
+            font_generation: fonts.map(|f| f.generation()),
+            palette: *palette,
+            item_icons,
+            filter: filter.map(str::to_string),
+        };
+        if self.key.as_ref() != Some(&key) {
+            self.messages.clear();
+            self.line_count = 0;
+            self.dropped_lines = 0;
+            self.log_count = log_count - logs.len();
+        }
+
+        // Lay out any messages that have been added since the last update.
+        let total = logs.len();
+        let new = (log_count - self.log_count).min(total);
+        for message in logs.skip(total - new) {
+            let layout = MessageLayout {
+                alpha: alpha(message),
+                first_line: self.dropped_lines + self.line_count,
+                lines: if filter.is_none_or(|player| mentions_player(message, player)) {
+                    wrap(ui, fonts, palette, item_icons, message.data(), key.width)
+                } else {
+                    vec![]
+                },
+            };
+            self.line_count += layout.lines.len();
+            self.messages.push_back(layout);
+        }
+
+        // Drop layouts for any messages that have fallen out of the buffer.
+        while self.messages.len() > total {
+            let layout = self.messages.pop_front().unwrap();
+            self.line_count -= layout.lines.len();
+            self.dropped_lines += layout.lines.len();
+        }
+
+        self.key = Some(key);
+        self.log_count = log_count;
+    }
+
+    /// Renders all the visible lines in the log, along with the context menu
+    /// for any link the player has right-clicked. Returns the action the
+    /// player chose from that menu, if any. `slot` is the name of the local
+    /// player.
+    pub fn render(&mut self, ui: &Ui, fonts: Option<&Fonts>, slot: &str) -> Option<LinkAction> {
+        let indent = hanging_indent(ui);
+        let mut clicked = None;
+        let clipper = ListClipper::new(self.line_count.try_into().unwrap());
+        let mut clip = clipper.begin(ui);
+        while clip.step() {
+            let start = clip.display_start().try_into().unwrap_or(0);
+            let end = clip.display_end().try_into().unwrap_or(0);
+            for (continuation, line, alpha) in self.lines_from(start).take(end - start) {
+                if continuation {
+                    let [x, y] = ui.cursor_pos();
+                    ui.set_cursor_pos([x + indent, y]);
+                }
+                if let Some(link) = write_line(ui, fonts, line, alpha) {
+                    clicked = Some(link);
+                }
+            }
+        }
+        drop(clip);
+
+        if let Some(link) = clicked {
+            self.context_link = Some(link);
+            ui.open_popup(LINK_POPUP_ID);
+        }
+
+        let mut action = None;
+        ui.popup(LINK_POPUP_ID, || {
+            if let Some(link) = &self.context_link {
+                action = link.render_context_menu(ui, slot);
+            }
+        });
+        action
+    }
+
+    /// Returns an iterator over the visual lines in the log starting at
+    /// `start`, along with whether each is a continuation of a previous line
+    /// and its opacity.
+    ///
+    /// This only walks the messages from `start` onwards, so rendering the
+    /// visible lines stays cheap however long the log gets.
+    fn lines_from(&self, start: usize) -> impl Iterator<Item = (bool, &[Fragment], u8)> {
+        let start = self.dropped_lines + start;
+        let index = self
+            .messages
+            .partition_point(|message| message.first_line + message.lines.len() <= start);
+        let skip = self
+            .messages
+            .get(index)
+            .map_or(0, |message| start - message.first_line);
+
+        self.messages
+            .range(index..)
+            .flat_map(|message| {
+                message
+                    .lines
+                    .iter()
+                    .enumerate()
+                    .map(|(i, line)| (i > 0, line.as_slice(), message.alpha))
+            })
+            .skip(skip)
+    }
+}
+
+/// Returns whether [message] mentions the player named [player].
+fn mentions_player(message: &Print, player: &str) -> bool {
+    message.data().iter().any(|part| {
+        matches!(part, RichText::Player { .. } | RichText::PlayerName { .. })
+            && part.to_string() == player
+    })
+}
+
+/// Returns the amount by which lines after the first in a wrapped message are
+/// indented, to distinguish them from the beginning of a new message.
+fn hanging_indent(ui: &Ui) -> f32 {
+    ui.current_font_size()
+}
+
+/// Wraps `parts` into lines that each fit within `width`.
+fn wrap(
+    ui: &Ui,
+    fonts: Option<&Fonts>,
+    palette: &Palette,
+    item_icons: bool,
+    parts: &[RichText],
+    width: f32,
+) -> Vec<Vec<Fragment>> {
+    let mut lines = vec![Vec::<Fragment>::new()];
+    let mut x = 0.0;
+    for part in parts {
+        let style = PartStyle::for_part(part, palette);
+        let link = Link::for_part(part).map(Arc::new);
+        let _font = fonts.filter(|_| style.bold).map(|f| ui.push_font(f.bold));
+
+        let mut text = part.to_string();
+        if item_icons && let Some(icon) = ItemClassification::of(part).and_then(|c| c.icon()) {
+            text = format!("{icon} {text}");
+        }
+
+        for (i, segment) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(vec![]);
+                x = 0.0;
+            }
+
+            for mut token in segment.split_inclusive(' ') {
+                // Don't count trailing whitespace against the width, since it's
+                // invisible at the end of a line anyway.
+                if x > 0.0
+                    && x + ui.calc_text_size(token.trim_end())[0] > line_width(ui, &lines, width)
+                {
+                    lines.push(vec![]);
+                    x = 0.0;
+                }
+
+                // If a single word is too wide to fit on a line on its own,
+                // break it wherever necessary. This is also how text without
+                // spaces (such as Chinese or Japanese) gets wrapped.
+                while x == 0.0
+                    && ui.calc_text_size(token.trim_end())[0] > line_width(ui, &lines, width)
+                {
+                    let split = fitting_prefix_len(ui, token, line_width(ui, &lines, width));
+                    if split == token.len() {
+                        break;
+                    }
+
+                    push_fragment(lines.last_mut().unwrap(), &token[..split], style, &link);
+                    lines.push(vec![]);
+                    token = &token[split..];
+                }
+
+                push_fragment(lines.last_mut().unwrap(), token, style, &link);
+                x += ui.calc_text_size(token)[0];
+            }
+        }
+    }
+    lines
+}
+
+/// Returns the width available to the last line in `lines`, taking the hanging
+/// indent into account.
+fn line_width(ui: &Ui, lines: &[Vec<Fragment>], width: f32) -> f32 {
+    if lines.len() == 1 {
+        width
+    } else {
+        width - hanging_indent(ui)
+    }
+}
+
+/// Returns the byte length of the longest prefix of `text` that fits within
+/// `limit`. This always includes at least one character, so that wrapping
+/// always makes progress.
+///
+/// A prefix's width only grows as it gets longer, so this binary searches the
+/// character boundaries rather than measuring every prefix.
+fn fitting_prefix_len(ui: &Ui, text: &str, limit: f32) -> usize {
+    let ends = text
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .collect::<Vec<_>>();
+    let Some(&first) = ends.first() else {
+        return 0;
+    };
+
+    let fitting = ends[1..].partition_point(|&end| ui.calc_text_size(&text[..end])[0] <= limit);
+    if fitting == 0 { first } else { ends[fitting] }
+}
+
+/// Adds `text` to the end of `line`, merging it into the previous fragment if
+/// they have the same style and link.
+fn push_fragment(line: &mut Vec<Fragment>, text: &str, style: PartStyle, link: &Option<Arc<Link>>) {
+    if text.is_empty() {
+        return;
+    }
+
+    match line.last_mut() {
+        Some(fragment) if fragment.style == style && fragment.link == *link => {
+            fragment.text.push_str(text)
+        }
+        _ => line.push(Fragment {
+            text: text.to_string(),
+            style,
+            link: link.clone(),
+        }),
+    }
+}
+
+/// Writes a single visual line of text to [ui]. Returns the link the player
+/// right-clicked, if any.
+fn write_line(ui: &Ui, fonts: Option<&Fonts>, line: &[Fragment], alpha: u8) -> Option<Arc<Link>> {
+    if line.is_empty() {
+        // Render an empty item so that the line still takes up space.
+        ui.text("");
+        return None;
+    }
+
+    let mut clicked = None;
+    let mut first = true;
+    for fragment in line {
+        if !first {
+            ui.same_line_with_spacing(0.0, 0.0);
+        }
+        first = false;
+
+        let style = &fragment.style;
+        let _font = fonts.filter(|_| style.bold).map(|f| ui.push_font(f.bold));
+        if let Some(background) = style.background {
+            let min = ui.cursor_screen_pos();
+            let size = ui.calc_text_size(&fragment.text);
+            ui.get_window_draw_list()
+                .add_rect(
+                    min,
+                    [min[0] + size[0], min[1] + size[1]],
+                    background.with_alpha(alpha),
+                )
+                .filled(true)
+                .build();
+        }
+
+        ui.text_colored(style.color.with_alpha(alpha).to_rgba_f32s(), &fragment.text);
+
+        // Underline links while they're hovered to make it clear that they're
+        // interactive.
+        let hovered = fragment.link.is_some() && ui.is_item_hovered();
+        if let Some(link) = &fragment.link
+            && hovered
+        {
+            link.render_tooltip(ui);
+            if ui.is_item_clicked_with_button(MouseButton::Right) {
+                clicked = Some(link.clone());
+            }
+        }
+
+        if style.underline || hovered {
+            let min = ui.item_rect_min();
+            let max = ui.item_rect_max();
+            // Draw the line just inside the text's bounding box so it doesn't
+            // collide with the next line of the log.
+            let y = max[1] - 1.0;
+            ui.get_window_draw_list()
+                .add_line([min[0], y], [max[0], y], style.color.with_alpha(alpha))
+                .build();
+        }
+    }
+    clicked
+}
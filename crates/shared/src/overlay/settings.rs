@@ -0,0 +1,91 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::{Error, Result};
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use super::theme::{Palette, Theme};
+use crate::{l10n::Locale, utils};
+
+/// The player's overlay preferences. Unlike [crate::config::Config], these are
+/// independent of any particular seed, so they're stored in their own file
+/// that the static randomizer never touches.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    /// The language to display the client's text in.
+    pub locale: Locale,
+
+    /// The font scale for the overlay UI.
+    pub font_scale: f32,
+
+    /// The window opacity for the overlay UI when it's not focused.
+    pub unfocused_window_opacity: f32,
+
+    /// The color theme to use for text in the overlay.
+    pub theme: Theme,
+
+    /// The palette to use when [theme] is [Theme::Custom].
+    pub custom_palette: Palette,
+
+    /// Whether to display an icon before item names indicating their
+    /// classification, in addition to coloring them.
+    pub item_icons: bool,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            locale: Default::default(),
+            font_scale: 1.8,
+            unfocused_window_opacity: 0.4,
+            theme: Default::default(),
+            custom_palette: Default::default(),
+            item_icons: false,
+        }
+    }
+}
+
+impl OverlaySettings {
+    /// Loads the settings from disk, or returns the defaults if they haven't
+    /// been saved yet or can't be loaded.
+    pub fn load() -> Self {
+        match Self::try_load() {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!("Failed to load overlay settings: {err:?}");
+                Default::default()
+            }
+        }
+    }
+
+    /// Like [load], but returns an error if the settings can't be loaded.
+    fn try_load() -> Result<Self> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(text) => json::from_str(&text).map_err(|err| {
+                Error::from(err).context(format!(
+                    "Failed to parse overlay settings {}",
+                    path.to_string_lossy()
+                ))
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the settings to disk.
+    pub fn save(&self) -> Result<()> {
+        Ok(fs::write(Self::path()?, json::to_string_pretty(self)?)?)
+    }
+
+    /// The path to the settings file.
+    fn path() -> Result<PathBuf> {
+        Ok(utils::mod_directory()?.join("apoverlay.json"))
+    }
+
+    /// Returns the palette for the current theme.
+    pub fn palette(&self) -> &Palette {
+        self.theme.palette(&self.custom_palette)
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use imgui::ImColor32;
+use serde::{Deserialize, Serialize};
+
+use crate::tr;
+
+/// A 24-bit RGB color that's serialized as a `#RRGGBB` string so that it's easy
+/// to edit by hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Returns this as an opaque imgui color.
+    pub const fn to_im(self) -> ImColor32 {
+        ImColor32::from_rgb(self.0, self.1, self.2)
+    }
+
+    /// Returns this as an array of floats, as used by imgui's color editors.
+    pub fn to_f32s(self) -> [f32; 3] {
+        [self.0, self.1, self.2].map(|c| f32::from(c) / 255.0)
+    }
+
+    /// Creates a color from an array of floats, as used by imgui's color
+    /// editors.
+    pub fn from_f32s(color: [f32; 3]) -> Self {
+        let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Rgb(r, g, b)
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}", self.0, self.1, self.2)
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(color: Rgb) -> String {
+        color.to_string()
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Rgb, Self::Error> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(format!("expected a color like #RRGGBB, was {value:?}"));
+        }
+
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| format!("expected a color like #RRGGBB, was {value:?}"))
+        };
+        Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+/// The set of colors used to render text in the overlay.
+///
+/// Every color here is rendered against the overlay's black background, so each
+/// should meet WCAG guidelines for contrast with black.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    /// The color for player names.
+    pub player: Rgb,
+
+    /// The color for names of filler items, as well as items whose
+    /// classification is unknown.
+    pub item: Rgb,
+
+    /// The color for names of progression items.
+    pub progression_item: Rgb,
+
+    /// The color for names of useful items.
+    pub useful_item: Rgb,
+
+    /// The color for names of trap items.
+    pub trap_item: Rgb,
+
+    /// The color for location and entrance names.
+    pub location: Rgb,
+
+    /// The color for text explicitly marked as black. Since the background is
+    /// black, this is really a dim gray.
+    pub black: Rgb,
+
+    /// The color for text explicitly marked as red.
+    pub red: Rgb,
+
+    /// The color for text explicitly marked as green.
+    pub green: Rgb,
+
+    /// The color for text explicitly marked as yellow.
+    pub yellow: Rgb,
+
+    /// The color for text explicitly marked as blue.
+    pub blue: Rgb,
+
+    /// The color for text explicitly marked as magenta.
+    pub magenta: Rgb,
+
+    /// The color for text explicitly marked as cyan.
+    pub cyan: Rgb,
+
+    /// The color for text explicitly marked as white, as well as text that has
+    /// no color at all.
+    pub white: Rgb,
+}
+
+impl Palette {
+    /// The overlay's standard palette.
+    pub const DEFAULT: Palette = Palette {
+        player: Rgb(0x82, 0xA9, 0xD4),
+        item: Rgb(0xBF, 0x9B, 0xBC),
+        progression_item: Rgb(0xAF, 0x99, 0xEF),
+        useful_item: Rgb(0x6D, 0x8B, 0xE8),
+        trap_item: Rgb(0xFA, 0x80, 0x72),
+        location: Rgb(0x34, 0xE2, 0xE2),
+        // This is the darkest gray that still meets WCAG guidelines for
+        // contrast with the black background of the overlay.
+        black: Rgb(0x9C, 0x9C, 0x9C),
+        red: Rgb(0xFF, 0x44, 0x44),
+        green: Rgb(0x8A, 0xE2, 0x43),
+        yellow: Rgb(0xFC, 0xE9, 0x4F),
+        blue: Rgb(0x82, 0xA9, 0xD4),
+        magenta: Rgb(0xBF, 0x9B, 0xBC),
+        cyan: Rgb(0x34, 0xE2, 0xE2),
+        white: Rgb(0xFF, 0xFF, 0xFF),
+    };
+
+    /// A palette for players with deuteranopia (reduced green sensitivity).
+    /// This is based on the Okabe-Ito palette, lightened where necessary to
+    /// contrast with black, and keeps the semantic colors on the blue/orange
+    /// axis.
+    pub const DEUTERANOPIA: Palette = Palette {
+        player: Rgb(0x56, 0xB4, 0xE9),
+        item: Rgb(0xBF, 0xA9, 0x8A),
+        progression_item: Rgb(0xE6, 0x9F, 0x00),
+        useful_item: Rgb(0xCC, 0x79, 0xA7),
+        trap_item: Rgb(0xD5, 0x5E, 0x00),
+        location: Rgb(0xF0, 0xE4, 0x42),
+        black: Rgb(0x9C, 0x9C, 0x9C),
+        red: Rgb(0xD5, 0x5E, 0x00),
+        green: Rgb(0x00, 0x9E, 0x73),
+        yellow: Rgb(0xF0, 0xE4, 0x42),
+        blue: Rgb(0x56, 0xB4, 0xE9),
+        magenta: Rgb(0xCC, 0x79, 0xA7),
+        cyan: Rgb(0x9A, 0xD7, 0xF5),
+        white: Rgb(0xFF, 0xFF, 0xFF),
+    };
+
+    /// A palette for players with protanopia (reduced red sensitivity). This is
+    /// like [Self::DEUTERANOPIA], but avoids reds entirely since they appear
+    /// especially dark.
+    pub const PROTANOPIA: Palette = Palette {
+        player: Rgb(0x56, 0xB4, 0xE9),
+        item: Rgb(0xC9, 0xB4, 0x8A),
+        progression_item: Rgb(0xF5, 0xB8, 0x2E),
+        useful_item: Rgb(0xD9, 0x9B, 0xC4),
+        trap_item: Rgb(0xB0, 0xB0, 0xFF),
+        location: Rgb(0xE8, 0xE8, 0xE8),
+        black: Rgb(0x9C, 0x9C, 0x9C),
+        red: Rgb(0xF5, 0xB8, 0x2E),
+        green: Rgb(0x4F, 0xC3, 0xA1),
+        yellow: Rgb(0xF0, 0xE4, 0x42),
+        blue: Rgb(0x56, 0xB4, 0xE9),
+        magenta: Rgb(0xD9, 0x9B, 0xC4),
+        cyan: Rgb(0x9A, 0xD7, 0xF5),
+        white: Rgb(0xFF, 0xFF, 0xFF),
+    };
+
+    /// A palette of bright, saturated colors with maximal contrast against the
+    /// black background.
+    pub const HIGH_CONTRAST: Palette = Palette {
+        player: Rgb(0x66, 0xCC, 0xFF),
+        item: Rgb(0xFF, 0xCC, 0xFF),
+        progression_item: Rgb(0xFF, 0x66, 0xFF),
+        useful_item: Rgb(0xAA, 0xAA, 0xFF),
+        trap_item: Rgb(0xFF, 0x88, 0x66),
+        location: Rgb(0x66, 0xFF, 0xFF),
+        black: Rgb(0xC0, 0xC0, 0xC0),
+        red: Rgb(0xFF, 0x66, 0x66),
+        green: Rgb(0x66, 0xFF, 0x66),
+        yellow: Rgb(0xFF, 0xFF, 0x66),
+        blue: Rgb(0x66, 0xCC, 0xFF),
+        magenta: Rgb(0xFF, 0x66, 0xFF),
+        cyan: Rgb(0x66, 0xFF, 0xFF),
+        white: Rgb(0xFF, 0xFF, 0xFF),
+    };
+
+    /// Returns mutable references to each color in this palette along with the
+    /// message key for its label, for use in the settings editor.
+    pub fn colors_mut(&mut self) -> [(&'static str, &mut Rgb); 14] {
+        [
+            ("color-player", &mut self.player),
+            ("color-progression-item", &mut self.progression_item),
+            ("color-useful-item", &mut self.useful_item),
+            ("color-trap-item", &mut self.trap_item),
+            ("color-filler-item", &mut self.item),
+            ("color-location", &mut self.location),
+            ("color-black", &mut self.black),
+            ("color-red", &mut self.red),
+            ("color-green", &mut self.green),
+            ("color-yellow", &mut self.yellow),
+            ("color-blue", &mut self.blue),
+            ("color-magenta", &mut self.magenta),
+            ("color-cyan", &mut self.cyan),
+            ("color-white", &mut self.white),
+        ]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The built-in themes the player can choose between, plus their own custom
+/// palette.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// [Palette::DEFAULT].
+    #[default]
+    Default,
+
+    /// [Palette::DEUTERANOPIA].
+    Deuteranopia,
+
+    /// [Palette::PROTANOPIA].
+    Protanopia,
+
+    /// [Palette::HIGH_CONTRAST].
+    HighContrast,
+
+    /// The player's own palette, which they can edit in the settings.
+    Custom,
+}
+
+impl Theme {
+    /// All themes, in the order they're displayed in the settings.
+    pub const ALL: [Theme; 5] = [
+        Theme::Default,
+        Theme::Deuteranopia,
+        Theme::Protanopia,
+        Theme::HighContrast,
+        Theme::Custom,
+    ];
+
+    /// Returns the human-readable name of this theme in the current locale.
+    pub fn name(&self) -> String {
+        match self {
+            Theme::Default => tr!("theme-default"),
+            Theme::Deuteranopia => tr!("theme-deuteranopia"),
+            Theme::Protanopia => tr!("theme-protanopia"),
+            Theme::HighContrast => tr!("theme-high-contrast"),
+            Theme::Custom => tr!("theme-custom"),
+        }
+    }
+
+    /// Returns the palette for this theme. `custom` is used for
+    /// [Theme::Custom].
+    pub fn palette<'a>(&self, custom: &'a Palette) -> &'a Palette {
+        match self {
+            Theme::Default => &Palette::DEFAULT,
+            Theme::Deuteranopia => &Palette::DEUTERANOPIA,
+            Theme::Protanopia => &Palette::PROTANOPIA,
+            Theme::HighContrast => &Palette::HIGH_CONTRAST,
+            Theme::Custom => custom,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+use anyhow::{Error, Result, anyhow};
+use log::*;
+
+thread_local! {
+    /// The number of [catch] calls active on this thread. While this is
+    /// nonzero, the panic hook logs panics without popping up a message box,
+    /// since they'll be displayed in the overlay instead.
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The first fatal error in a game hook callback that hasn't yet been passed
+/// along to the core. Hook callbacks don't have access to the core, so it picks
+/// this up on its next update instead.
+static HOOK_ERROR: Mutex<Option<Error>> = Mutex::new(None);
+
+/// Returns whether a panic on the current thread is going to be caught by
+/// [catch].
+pub(crate) fn is_catching() -> bool {
+    CATCHING.get() > 0
+}
+
+/// Returns a human-readable description of a panic's payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        format!("{payload:?}")
+    }
+}
+
+/// Runs `f` and converts any panic it raises into an error. `context`
+/// describes what was running, for use in the error message.
+///
+/// The panic is still logged with its backtrace, but it doesn't pop up a
+/// message box.
+pub(crate) fn catch<T>(context: &str, f: impl FnOnce() -> T) -> Result<T> {
+    CATCHING.set(CATCHING.get() + 1);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(CATCHING.get() - 1);
+    result.map_err(|payload| anyhow!("Panic in {context}: {}", panic_message(&*payload)))
+}
+
+/// Runs `f`, a callback for a hook into the game's code, and returns its
+/// result. If it panics, this returns None instead and reports the panic as a
+/// fatal error in the overlay the next time the core updates.
+///
+/// Panics must not unwind out of a hook callback into the game's code, so
+/// every callback should be wrapped in this.
+pub fn catch_hook_panic<T>(context: &str, f: impl FnOnce() -> T) -> Option<T> {
+    match catch(context, f) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            report_hook_error(err);
+            None
+        }
+    }
+}
+
+/// Reports a fatal error from a game hook callback. It's displayed in the
+/// overlay the next time the core updates.
+pub fn report_hook_error(err: Error) {
+    error!("Error in hook: {err:?}");
+    let mut hook_error = HOOK_ERROR.lock().unwrap_or_else(|e| e.into_inner());
+    if hook_error.is_none() {
+        *hook_error = Some(err);
+    }
+}
+
+/// Takes the error reported by [report_hook_error] or [catch_hook_panic], if
+/// one has occurred since the last call.
+pub(crate) fn take_hook_error() -> Option<Error> {
+    HOOK_ERROR.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, mpsc};
+use std::thread;
+
+use anyhow::Result;
+use chrono::prelude::*;
+use log::*;
+
+use crate::utils;
+
+/// The number of backups to keep for each character. Older backups are deleted.
+const MAX_BACKUPS: usize = 20;
+
+/// The extension for backup files.
+const EXTENSION: &str = "bin";
+
+/// Returns the directory containing all save data backups.
+pub(crate) fn backup_root() -> Result<PathBuf> {
+    Ok(utils::mod_directory()?.join("save-backups"))
+}
+
+/// Sends backups to the thread that writes them. See [write_backup].
+static WRITER: LazyLock<mpsc::Sender<(PathBuf, Vec<u8>)>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel::<(PathBuf, Vec<u8>)>();
+    thread::spawn(move || {
+        for (dir, bytes) in receiver {
+            if let Err(err) = write_backup_sync(&dir, &bytes) {
+                warn!("Failed to back up save data: {err:?}");
+            }
+        }
+    });
+    sender
+});
+
+/// Returns the directory containing backups of the save data for every
+/// character playing `slot` in `seed`.
+pub(crate) fn backup_dir(seed: &str, slot: &str) -> Result<PathBuf> {
+    Ok(backup_root()?.join(sanitize(seed)).join(sanitize(slot)))
+}
+
+/// Returns the directory within `slot_dir`, as returned by [backup_dir],
+/// that contains backups of `character`'s save data.
+pub(crate) fn character_dir(slot_dir: &Path, character: u64) -> PathBuf {
+    slot_dir.join(format!("{character:016x}"))
+}
+
+/// Writes `bytes` as a new backup in `dir` and deletes the oldest backups
+/// beyond [MAX_BACKUPS]. This runs on a background thread so that it doesn't
+/// stall the game while it's saving. Backups are written one at a time, in
+/// the order they're requested.
+pub(crate) fn write_backup(dir: PathBuf, bytes: Vec<u8>) {
+    // The thread only exits if it panicked, in which case there's nothing more
+    // we can do.
+    let _ = WRITER.send((dir, bytes));
+}
+
+/// Like [write_backup], but runs on the current thread.
+fn write_backup_sync(dir: &Path, bytes: &[u8]) -> Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let stem = Local::now().format("%Y-%m-%d-%H-%M-%S").to_string();
+    let mut path = dir.join(format!("{stem}.{EXTENSION}"));
+    // If we save more than once a second, add a suffix to keep the names
+    // unique.
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = dir.join(format!("{stem}-{suffix}.{EXTENSION}"));
+    }
+    fs::write(&path, bytes)?;
+    info!("Backed up save data to {}", path.to_string_lossy());
+
+    let backups = backups(dir)?;
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in backups.iter().rev().take(excess) {
+        fs::remove_file(old)?;
+    }
+    Ok(())
+}
+
+/// Returns the paths of all backups in `dir`, newest first.
+pub(crate) fn backups(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut backups = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+        .collect::<Vec<_>>();
+    sort_newest_first(&mut backups);
+    Ok(backups)
+}
+
+/// Returns the paths of all backups for every character in `slot_dir`, as
+/// returned by [backup_dir], newest first. This includes backups written
+/// directly to `slot_dir` before they were kept apart by character.
+pub(crate) fn all_backups(slot_dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(slot_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut all = backups(slot_dir)?;
+    for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+        if path.is_dir() {
+            all.extend(backups(&path)?);
+        }
+    }
+    sort_newest_first(&mut all);
+    Ok(all)
+}
+
+/// Sorts `backups` by modification time, newest first.
+fn sort_newest_first(backups: &mut [PathBuf]) {
+    backups.sort_by_cached_key(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+    backups.reverse();
+}
+
+/// Replaces any characters in `name` that might not be valid in a file name.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use archipelago_rs as ap;
+
+use crate::{Core, tr, utils};
+
+/// The maximum number of added or removed array entries to list individually
+/// in a diff.
+const MAX_LISTED_ENTRIES: usize = 10;
+
+/// An import that the player has reviewed but not yet applied.
+pub(crate) struct PendingImport {
+    /// The validated save data to import.
+    data: json::Value,
+
+    /// The save data at the time the import was reviewed. If the current data
+    /// no longer matches this, the reviewed diff is out of date.
+    baseline: json::Value,
+}
+
+/// Handles the `!savedata` command, which exports the current save data to a
+/// JSON file or imports it back in after it's been edited.
+pub(crate) fn handle<C: Core>(core: &mut C, arg: Option<&str>) {
+    let result = match arg.map(str::trim) {
+        Some("export") => export(core),
+        Some("import") => review_import(core),
+        Some("import apply") => apply_import(core),
+        _ => {
+            core.log(tr!("savedata-usage"));
+            Ok(())
+        }
+    };
+
+    if let Err(err) = result {
+        core.log(ap::RichText::Color {
+            text: tr!("savedata-failed", error = format!("{err:#}")),
+            color: ap::TextColor::Red,
+        });
+    }
+}
+
+/// Returns the path to the file that save data is exported to and imported
+/// from.
+fn path() -> Result<PathBuf> {
+    Ok(utils::mod_directory()?.join("savedata.json"))
+}
+
+/// Returns the current save data as JSON, or an error if it's not available.
+fn current<C: Core>(core: &C) -> Result<json::Value> {
+    core.save_data_json()
+        .with_context(|| tr!("savedata-not-loaded"))
+}
+
+/// Writes the current save data to [path].
+fn export<C: Core>(core: &mut C) -> Result<()> {
+    let data = current(core)?;
+    let path = path()?;
+    fs::write(&path, json::to_string_pretty(&data)?)?;
+    core.log(tr!("savedata-exported", path = path.to_string_lossy()));
+    Ok(())
+}
+
+/// Validates the save data in [path] and shows how it differs from the current
+/// data, without applying it.
+fn review_import<C: Core>(core: &mut C) -> Result<()> {
+    let store = core
+        .save_store()
+        .with_context(|| tr!("savedata-unsupported"))?;
+    let baseline = current(core)?;
+    let path = path()?;
+    let text = fs::read_to_string(&path)
+        .with_context(|| tr!("savedata-read-failed", path = path.to_string_lossy()))?;
+    let data = store
+        .validate_json(json::from_str(&text).with_context(|| tr!("savedata-invalid-json"))?)?;
+    validate_for_session(core, &data)?;
+
+    let mut changes = vec![];
+    diff("", &baseline, &data, &mut changes);
+    if changes.is_empty() {
+        core.base_mut().set_pending_import(None);
+        core.log(tr!("savedata-unchanged"));
+        return Ok(());
+    }
+
+    core.log(tr!(
+        "savedata-diff",
+        path = path.to_string_lossy(),
+        changes = changes.join("\n"),
+    ));
+    core.base_mut()
+        .set_pending_import(Some(PendingImport { data, baseline }));
+    Ok(())
+}
+
+/// Applies the import reviewed by [review_import]. The import stays pending if
+/// it can't be applied, so the player can try again once the problem is fixed.
+fn apply_import<C: Core>(core: &mut C) -> Result<()> {
+    let Some(pending) = core.base().pending_import() else {
+        bail!(tr!("savedata-no-pending"));
+    };
+    let store = core
+        .save_store()
+        .with_context(|| tr!("savedata-unsupported"))?;
+    if current(core)? != pending.baseline {
+        bail!(tr!("savedata-changed"));
+    }
+
+    store.import_json(pending.data.clone())?;
+    core.base_mut().set_pending_import(None);
+    core.log(tr!("savedata-imported"));
+    Ok(())
+}
+
+/// Checks that `data` makes sense for the current Archipelago session.
+fn validate_for_session<C: Core>(core: &C, data: &json::Value) -> Result<()> {
+    let Some(client) = core.client() else {
+        return Ok(());
+    };
+
+    if let Some(items_granted) = data.get("items_granted").and_then(|v| v.as_u64())
+        && items_granted > client.received_items().len() as u64
+    {
+        bail!(tr!(
+            "savedata-too-many-items",
+            items_granted = items_granted,
+            received = client.received_items().len(),
+        ));
+    }
+
+    if let Some(seed) = data.get("seed").and_then(|v| v.as_str())
+        && client.seed_name() != seed
+    {
+        bail!(tr!(
+            "savedata-seed-mismatch",
+            seed = seed,
+            room_seed = client.seed_name(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Adds a human-readable line to `changes` for each difference between `old`
+/// and `new`. `path` is the location of these values in the overall document.
+fn diff(path: &str, old: &json::Value, new: &json::Value, changes: &mut Vec<String>) {
+    use json::Value::*;
+    match (old, new) {
+        (Object(old), Object(new)) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff(
+                    &path,
+                    old.get(key).unwrap_or(&Null),
+                    new.get(key).unwrap_or(&Null),
+                    changes,
+                );
+            }
+        }
+        // Arrays in save data are sets, so compare them by membership rather
+        // than by position.
+        (Array(old), Array(new)) => {
+            let added = new.iter().filter(|v| !old.contains(v)).collect::<Vec<_>>();
+            let removed = old.iter().filter(|v| !new.contains(v)).collect::<Vec<_>>();
+            if !added.is_empty() {
+                let change = tr!("savedata-diff-added", path = path, entries = list(&added));
+                changes.push(format!("  {change}"));
+            }
+            if !removed.is_empty() {
+                let change = tr!(
+                    "savedata-diff-removed",
+                    path = path,
+                    entries = list(&removed)
+                );
+                changes.push(format!("  {change}"));
+            }
+        }
+        _ if old != new => {
+            let change = tr!("savedata-diff-changed", path = path, old = old, new = new);
+            changes.push(format!("  {change}"));
+        }
+        _ => {}
+    }
+}
+
+/// Returns a short description of `entries`, listing them individually if
+/// there aren't too many.
+fn list(entries: &[&json::Value]) -> String {
+    if entries.len() > MAX_LISTED_ENTRIES {
+        tr!("savedata-entries", count = entries.len())
+    } else {
+        entries
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
@@ -0,0 +1,382 @@
+//! The binary format for the data the mod stores in the player's save file.
+//!
+//! Every blob starts with a header: [MAGIC], then a CRC-32 checksum of
+//! everything after it as a little-endian `u32`, then the format version as a
+//! variable-length integer. The rest is the bincode encoding of that version's
+//! layout. The magic tag and checksum keep data written by another mod, or
+//! damaged on disk, from being decoded as nonsense.
+//!
+//! Older saves are still readable, although they can't be validated:
+//!
+//! * Saves written before the checksum existed start with [HEADER_MARKER]
+//!   followed by the format version, then the payload.
+//!
+//! * Saves written before any header existed are treated as version 0. They can
+//!   be told apart because no valid version 0 save starts with [HEADER_MARKER]
+//!   or [MAGIC].
+
+use std::{fmt, io};
+
+use anyhow::{Error, Result, bail};
+use bincode::error::DecodeError as BincodeDecodeError;
+use bincode::{Decode, Encode};
+
+use crate::tr;
+
+/// The configuration for the binary encoding of the save data.
+const CONFIG: bincode::config::Configuration = bincode::config::standard();
+
+/// The tag at the start of every save blob written by this version of the mod.
+/// Its first byte is never the first byte of a version 0 save, for the same
+/// reason as [HEADER_MARKER].
+const MAGIC: &[u8; 4] = b"\xFEAPS";
+
+/// The first byte of save blobs written before [MAGIC] was added. Bincode never
+/// starts a variable-length integer with this byte, and every historical layout
+/// begins with one, so it can't be mistaken for a version 0 save.
+const HEADER_MARKER: u8 = 0xFF;
+
+/// A type that's stored in the player's save file in a versioned format.
+pub trait VersionedSave: Encode + Decode<()> + Sized {
+    /// The version of the format that this type represents. This must be
+    /// increased whenever the layout changes, and the old layout must be added
+    /// to [Self::migrate].
+    const VERSION: u32;
+
+    /// Decodes `bytes`, which are in the layout used by `version`, and upgrades
+    /// the result to the current layout. `version` is always less than
+    /// [Self::VERSION].
+    ///
+    /// This typically decodes a frozen copy of the old layout and converts it
+    /// through each intermediate layout in turn, so that each migration only
+    /// needs to know about the layout immediately before it.
+    fn migrate(version: u32, bytes: &[u8]) -> Result<Self>;
+}
+
+/// An error decoding a save blob.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The save was written by a newer version of the mod whose format this
+    /// version doesn't understand.
+    TooNew {
+        /// The version of the format the save was written in.
+        version: u32,
+
+        /// The newest version this client supports.
+        supported: u32,
+    },
+
+    /// The save's checksum didn't match its contents, which means that it was
+    /// damaged or overwritten by something other than this mod.
+    ChecksumMismatch {
+        /// The checksum stored in the save.
+        expected: u32,
+
+        /// The checksum of the save's actual contents.
+        actual: u32,
+    },
+
+    /// The save couldn't be decoded.
+    Invalid(Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooNew { version, supported } => write!(
+                f,
+                "{}",
+                tr!(
+                    "save-data-too-new",
+                    version = version,
+                    supported = supported
+                )
+            ),
+            DecodeError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "{}",
+                tr!(
+                    "save-data-checksum-mismatch",
+                    expected = format!("{expected:08x}"),
+                    actual = format!("{actual:08x}")
+                )
+            ),
+            DecodeError::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<Error> for DecodeError {
+    fn from(err: Error) -> Self {
+        // Unwrap errors from nested blobs that were passed through bincode by
+        // [nested_error].
+        match err.downcast::<BincodeDecodeError>() {
+            Ok(BincodeDecodeError::Io { inner, .. })
+                if inner.get_ref().is_some_and(|err| err.is::<DecodeError>()) =>
+            {
+                *inner.into_inner().unwrap().downcast().unwrap()
+            }
+            Ok(err) => DecodeError::Invalid(err.into()),
+            Err(err) => DecodeError::Invalid(err),
+        }
+    }
+}
+
+/// Wraps an error from decoding a nested save blob so that it can be returned
+/// from a [Decode] implementation. [decode] unwraps it again, so that a nested
+/// [DecodeError::TooNew] is reported as such rather than as
+/// [DecodeError::Invalid].
+pub fn nested_error(err: DecodeError) -> BincodeDecodeError {
+    BincodeDecodeError::Io {
+        inner: io::Error::other(err),
+        additional: 0,
+    }
+}
+
+/// Encodes `data` in the current format, with a header.
+pub fn encode<T: VersionedSave>(data: &T) -> Result<Vec<u8>> {
+    let mut body = bincode::encode_to_vec(T::VERSION, CONFIG)?;
+    body.extend(bincode::encode_to_vec(data, CONFIG)?);
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(crc32(&body).to_le_bytes());
+    bytes.extend(body);
+    Ok(bytes)
+}
+
+/// Decodes `bytes` in any format version up to the current one, migrating it
+/// if necessary. Returns [DecodeError::ChecksumMismatch] if the blob has a
+/// checksum and it doesn't match.
+pub fn decode<T: VersionedSave>(bytes: &[u8]) -> Result<T, DecodeError> {
+    if let Some(rest) = bytes.strip_prefix(MAGIC) {
+        let Some((checksum, body)) = rest.split_first_chunk::<4>() else {
+            return Err(Error::msg("Save data is too short to contain a checksum").into());
+        };
+
+        let expected = u32::from_le_bytes(*checksum);
+        let actual = crc32(body);
+        if expected != actual {
+            return Err(DecodeError::ChecksumMismatch { expected, actual });
+        }
+        decode_versioned(body)
+    } else if let Some((&HEADER_MARKER, rest)) = bytes.split_first() {
+        decode_versioned(rest)
+    } else {
+        Ok(T::migrate(0, bytes)?)
+    }
+}
+
+/// Decodes `bytes`, which start with the format version followed by the
+/// payload in that version's layout.
+fn decode_versioned<T: VersionedSave>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let (version, header_size) = bincode::decode_from_slice::<u32, _>(bytes, CONFIG)
+        .map_err(|err| Error::from(err).context("Failed to decode save data header"))?;
+    let payload = &bytes[header_size..];
+    if version > T::VERSION {
+        Err(DecodeError::TooNew {
+            version,
+            supported: T::VERSION,
+        })
+    } else if version < T::VERSION {
+        Ok(T::migrate(version, payload)?)
+    } else {
+        Ok(decode_exact(payload)?)
+    }
+}
+
+/// Decodes `bytes` as a `T`, and returns an error if there are any bytes left
+/// over. This is useful for implementing [VersionedSave::migrate].
+pub fn decode_exact<T: Decode<()>>(bytes: &[u8]) -> Result<T> {
+    let (data, size) = bincode::decode_from_slice(bytes, CONFIG)?;
+    if size != bytes.len() {
+        bail!(
+            "Archipelago save data had {} extra bytes! This probably means that you tried to \
+             load a save file created by a different mod entirely.",
+            bytes.len() - size
+        );
+    }
+    Ok(data)
+}
+
+/// Returns the CRC-32 (IEEE) checksum of `bytes`. Save blobs are small and only
+/// checksummed when the game saves or loads, so this doesn't bother with a
+/// lookup table.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A save whose current layout is version 2. Versions 0 and 1 only stored
+    /// [count](TestSave::count).
+    #[derive(Debug, PartialEq, Encode, Decode)]
+    struct TestSave {
+        count: u32,
+        name: String,
+    }
+
+    impl VersionedSave for TestSave {
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, bytes: &[u8]) -> Result<Self> {
+            match version {
+                0 | 1 => Ok(TestSave {
+                    count: decode_exact(bytes)?,
+                    name: "migrated".into(),
+                }),
+                _ => bail!("Unknown test save version {version}"),
+            }
+        }
+    }
+
+    /// A save that stores a [TestSave] as a nested blob, the way
+    /// [crate::SaveData] stores its game-specific data.
+    #[derive(Debug)]
+    struct OuterSave {
+        inner: TestSave,
+    }
+
+    impl Encode for OuterSave {
+        fn encode<E: bincode::enc::Encoder>(
+            &self,
+            encoder: &mut E,
+        ) -> Result<(), bincode::error::EncodeError> {
+            encode(&self.inner).unwrap().encode(encoder)
+        }
+    }
+
+    impl<Context> Decode<Context> for OuterSave {
+        fn decode<D: bincode::de::Decoder<Context = Context>>(
+            decoder: &mut D,
+        ) -> Result<Self, BincodeDecodeError> {
+            let inner: Vec<u8> = Decode::decode(decoder)?;
+            Ok(OuterSave {
+                inner: decode(&inner).map_err(nested_error)?,
+            })
+        }
+    }
+
+    impl VersionedSave for OuterSave {
+        const VERSION: u32 = 1;
+
+        fn migrate(version: u32, _bytes: &[u8]) -> Result<Self> {
+            bail!("Unknown outer save version {version}")
+        }
+    }
+
+    /// Returns a blob in the current format with the given version and
+    /// payload.
+    fn blob(version: u32, payload: impl Encode) -> Vec<u8> {
+        let mut body = bincode::encode_to_vec(version, CONFIG).unwrap();
+        body.extend(bincode::encode_to_vec(payload, CONFIG).unwrap());
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(crc32(&body).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn round_trips_current_version() {
+        let save = TestSave {
+            count: 300,
+            name: "current".into(),
+        };
+        let decoded = decode::<TestSave>(&encode(&save).unwrap()).unwrap();
+        assert_eq!(decoded, save);
+    }
+
+    #[test]
+    fn encodes_with_magic_and_checksum() {
+        let save = TestSave {
+            count: 300,
+            name: "current".into(),
+        };
+        let bytes = encode(&save).unwrap();
+        assert!(bytes.starts_with(MAGIC));
+        assert_eq!(bytes, blob(2, &save));
+    }
+
+    #[test]
+    fn rejects_flipped_byte() {
+        let mut bytes = encode(&TestSave {
+            count: 300,
+            name: "current".into(),
+        })
+        .unwrap();
+        *bytes.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(
+            decode::<TestSave>(&bytes),
+            Err(DecodeError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_checksum() {
+        let bytes = [MAGIC.as_slice(), &[0, 0]].concat();
+        assert!(matches!(
+            decode::<TestSave>(&bytes),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn decodes_headerless_version_0() {
+        let bytes = bincode::encode_to_vec(300u32, CONFIG).unwrap();
+        let decoded = decode::<TestSave>(&bytes).unwrap();
+        assert_eq!(decoded.count, 300);
+        assert_eq!(decoded.name, "migrated");
+    }
+
+    #[test]
+    fn decodes_version_1_with_header_marker() {
+        let mut bytes = vec![HEADER_MARKER];
+        bytes.extend(bincode::encode_to_vec(1u32, CONFIG).unwrap());
+        bytes.extend(bincode::encode_to_vec(300u32, CONFIG).unwrap());
+
+        let decoded = decode::<TestSave>(&bytes).unwrap();
+        assert_eq!(decoded.count, 300);
+        assert_eq!(decoded.name, "migrated");
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let bytes = blob(3, 300u32);
+        assert!(matches!(
+            decode::<TestSave>(&bytes),
+            Err(DecodeError::TooNew {
+                version: 3,
+                supported: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_newer_nested_version() {
+        let bytes = blob(1, blob(3, 300u32));
+        assert!(matches!(
+            decode::<OuterSave>(&bytes),
+            Err(DecodeError::TooNew {
+                version: 3,
+                supported: 2
+            })
+        ));
+    }
+}
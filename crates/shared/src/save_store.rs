@@ -0,0 +1,790 @@
+use std::collections::HashSet;
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+use anyhow::{Context, Error, Result, bail};
+use bincode::de::{Decode, Decoder};
+use bincode::enc::{Encode, Encoder};
+use bincode::error::{DecodeError as BincodeDecodeError, EncodeError};
+use log::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::save_format::{self, DecodeError, VersionedSave};
+use crate::{save_backup, tr};
+
+/// Data that's saved and loaded along with the player's game save. The fields
+/// every game needs are stored directly, and anything specific to one game is
+/// stored in [game].
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SaveData<T> {
+    /// The number of Archipelago items that have been granted to this player
+    /// from foreign games throughout the course of this run.
+    pub items_granted: usize,
+
+    /// The set of Archipelago locations that this player has accessed so far in
+    /// this game. We don't strictly need to track this, but it helps us avoid
+    /// being overly chatty with the server.
+    pub locations: HashSet<i64>,
+
+    /// The Archipelago seed this save file was last connected to. This is used
+    /// to verify that the player doesn't accidentally corrupt a save by loading
+    /// into it while connected to the wrong multiworld.
+    pub seed: Option<String>,
+
+    /// The number of deaths that player has experienced since last sending a
+    /// death link.
+    pub deaths: u8,
+
+    /// Data specific to the game being played. This is versioned separately
+    /// from the common fields, so games can change its layout without touching
+    /// the shared format.
+    pub game: T,
+
+    /// A random identifier for the character this data belongs to, assigned
+    /// the first time it's saved. This keeps the backups of different
+    /// characters playing the same seed and slot apart.
+    #[serde(default)]
+    pub character: Option<u64>,
+}
+
+// The game-specific data is encoded as a nested versioned blob so that its
+// version is checked independently of the common fields.
+impl<T: VersionedSave> Encode for SaveData<T> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.items_granted.encode(encoder)?;
+        self.locations.encode(encoder)?;
+        self.seed.encode(encoder)?;
+        self.deaths.encode(encoder)?;
+        save_format::encode(&self.game)
+            .map_err(|err| EncodeError::OtherString(err.to_string()))?
+            .encode(encoder)?;
+        self.character.encode(encoder)
+    }
+}
+
+impl<T: VersionedSave, Context> Decode<Context> for SaveData<T> {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, BincodeDecodeError> {
+        let SaveDataV2(mut data) = Decode::decode(decoder)?;
+        data.character = Decode::decode(decoder)?;
+        Ok(data)
+    }
+}
+
+impl<T: VersionedSave + Default> VersionedSave for SaveData<T> {
+    const VERSION: u32 = 3;
+
+    fn migrate(version: u32, bytes: &[u8]) -> Result<Self> {
+        match version {
+            // Versions 0 and 1 only differ in whether they have a header.
+            0 | 1 => Ok(save_format::decode_exact::<SaveDataV1>(bytes)?.into()),
+            2 => Ok(save_format::decode_exact::<SaveDataV2<T>>(bytes)?.0),
+            _ => bail!("Unknown save data version {version}"),
+        }
+    }
+}
+
+/// The layout of [SaveData] before it had [SaveData::character], which is the
+/// current layout without that field at the end. This must not be changed,
+/// since it's used to read old saves.
+struct SaveDataV2<T>(SaveData<T>);
+
+impl<T: VersionedSave, Context> Decode<Context> for SaveDataV2<T> {
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, BincodeDecodeError> {
+        let items_granted = Decode::decode(decoder)?;
+        let locations = Decode::decode(decoder)?;
+        let seed = Decode::decode(decoder)?;
+        let deaths = Decode::decode(decoder)?;
+        let game: Vec<u8> = Decode::decode(decoder)?;
+        let game = save_format::decode(&game).map_err(save_format::nested_error)?;
+        Ok(Self(SaveData {
+            items_granted,
+            locations,
+            seed,
+            deaths,
+            game,
+            character: None,
+        }))
+    }
+}
+
+/// The layout of [SaveData] before it had game-specific data. This must not be
+/// changed, since it's used to read old saves.
+#[derive(bincode::Decode)]
+struct SaveDataV1 {
+    items_granted: usize,
+    locations: HashSet<i64>,
+    seed: Option<String>,
+    deaths: u8,
+}
+
+impl<T: Default> From<SaveDataV1> for SaveData<T> {
+    fn from(data: SaveDataV1) -> Self {
+        Self {
+            items_granted: data.items_granted,
+            locations: data.locations,
+            seed: data.seed,
+            deaths: data.deaths,
+            game: Default::default(),
+            character: None,
+        }
+    }
+}
+
+/// A notification that the game has loaded, passed from a [SaveHook] to its
+/// [SaveStore].
+pub enum LoadEvent<'a> {
+    /// The player loaded a save file that contains data from this mod.
+    SavedData(&'a [u8]),
+
+    /// The player returned to the main menu.
+    MainMenu,
+
+    /// Any other load, such as starting a new game.
+    Other,
+}
+
+/// The game-specific hooks a [SaveStore] needs to store its data in the
+/// player's save file.
+pub trait SaveHook {
+    /// Registers `on_save` to be called whenever the game saves, returning the
+    /// data to store in the save file if any, and `on_load` to be called
+    /// whenever the game loads. These hooks are never unregistered.
+    ///
+    /// ## Safety
+    ///
+    /// Follow all ilhook safety guidelines.
+    unsafe fn on_save_load(
+        on_save: impl Fn() -> Option<Vec<u8>> + Send + Sync + 'static,
+        on_load: impl Fn(LoadEvent) + Send + Sync + 'static,
+    );
+
+    /// Returns whether the player is currently loaded into a game, as opposed
+    /// to being on the main menu.
+    fn is_in_game() -> bool;
+}
+
+/// A problem with the save data that was just loaded, which the player may be
+/// able to fix by restoring a backup.
+#[derive(Debug, Clone)]
+pub struct SaveProblem {
+    /// A human-readable description of the problem.
+    pub message: String,
+
+    /// The most recent backup for the current character, if there is one.
+    pub backup: Option<BackupInfo>,
+
+    /// Whether the loaded data didn't say which character it belongs to, for
+    /// example because it was too damaged to read, so [backup] is the most
+    /// recent one for any character playing this seed and slot.
+    pub any_character: bool,
+
+    /// Whether the loaded data couldn't be read at all, so dismissing this
+    /// problem throws it away and starts over with empty data, which grants
+    /// every item again.
+    pub discards_save: bool,
+}
+
+/// Information about a single backup of the save data.
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    /// The path to the backup file.
+    pub path: PathBuf,
+
+    /// The value of [SaveData::items_granted] in the backup.
+    pub items_granted: usize,
+
+    /// The number of [SaveData::locations] in the backup.
+    pub locations: usize,
+}
+
+/// The singleton storage for a game's [SaveData], which keeps it in sync with
+/// the player's save file through `H`.
+pub struct SaveStore<T, H> {
+    /// The current save data. This isn't meaningful unless the player is
+    /// loaded into a game.
+    data: LazyLock<RwLock<SaveData<T>>>,
+
+    /// The raw save data from a save file that couldn't be used, either
+    /// because it was written by a newer version of the mod or because it's
+    /// damaged. This is written back out unchanged when the game saves so that
+    /// it isn't lost before the player has decided what to do about it.
+    unreadable: Mutex<Option<Vec<u8>>>,
+
+    /// The seed and slot the player is currently playing, which along with
+    /// [SaveData::character] determine where backups are stored. None if they
+    /// aren't known yet.
+    session: Mutex<Option<(String, String)>>,
+
+    /// The encoded data that was most recently backed up, used to avoid
+    /// writing identical backups every time the game autosaves.
+    last_backup: Mutex<Option<Vec<u8>>>,
+
+    /// A problem with the most recently loaded save data that the player
+    /// hasn't dealt with yet.
+    problem: Mutex<Option<SaveProblem>>,
+
+    /// The hooks for the game this is storing data for.
+    hook: PhantomData<fn() -> H>,
+}
+
+impl<T: VersionedSave + Default + Send + Sync + 'static, H: SaveHook> SaveStore<T, H> {
+    /// Creates a new, empty [SaveStore].
+    pub const fn new() -> Self {
+        Self {
+            data: LazyLock::new(RwLock::default),
+            unreadable: Mutex::new(None),
+            session: Mutex::new(None),
+            last_backup: Mutex::new(None),
+            problem: Mutex::new(None),
+            hook: PhantomData,
+        }
+    }
+
+    /// Registers hooks for loading and unloading saves. These hooks are never
+    /// unregistered.
+    ///
+    /// ## Safety
+    ///
+    /// Follow all ilhook safety guidelines.
+    pub unsafe fn hook(&'static self) {
+        unsafe {
+            H::on_save_load(
+                move || crate::catch_hook_panic("the save hook", || self.on_save()).flatten(),
+                move |event| {
+                    crate::catch_hook_panic("the load hook", || self.on_load(event));
+                },
+            )
+        }
+    }
+
+    /// Returns a read-only reference to the [SaveData], or None if the player
+    /// isn't currently loaded into a game.
+    pub fn instance(&self) -> Option<RwLockReadGuard<'_, SaveData<T>>> {
+        // The save data is only meaningful when the player is loaded into an
+        // actual game, *not* on the main menu. No file is loaded when the
+        // player starts a new game, so we can't just track whether a save file
+        // has been loaded.
+        if H::is_in_game() {
+            Some(self.data.read().unwrap_or_else(PoisonError::into_inner))
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the [SaveData], or None if the player
+    /// isn't currently loaded into a game.
+    pub fn instance_mut(&self) -> Option<RwLockWriteGuard<'_, SaveData<T>>> {
+        // See above.
+        if H::is_in_game() {
+            Some(self.data.write().unwrap_or_else(PoisonError::into_inner))
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the [SaveData] with `data`. Returns an error if the player
+    /// isn't currently loaded into a game.
+    pub fn replace(&self, data: SaveData<T>) -> Result<()> {
+        let Some(mut save) = self.instance_mut() else {
+            bail!("No save is loaded");
+        };
+        *save = data;
+        // The player has explicitly chosen new data, so don't keep writing
+        // back data from a newer version.
+        *self
+            .unreadable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+        Ok(())
+    }
+
+    /// Returns the data to store in the save file when the game saves.
+    fn on_save(&self) -> Option<Vec<u8>> {
+        if let Some(bytes) = self
+            .unreadable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+        {
+            return Some(bytes);
+        }
+
+        let mut data = self.instance_mut()?;
+        // New characters get their identity the first time they're saved.
+        let character = *data.character.get_or_insert_with(new_character_id);
+        match save_format::encode(&*data) {
+            Ok(bytes) => {
+                self.back_up(data.seed.as_deref(), Some(character), &bytes);
+                Some(bytes)
+            }
+            Err(err) => {
+                warn!("Failed to encode save data: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Updates the save data when the game loads.
+    fn on_load(&self, event: LoadEvent) {
+        let bytes = match event {
+            LoadEvent::SavedData(bytes) => bytes,
+            LoadEvent::MainMenu => {
+                // If the player goes back to the main menu, reset the granted
+                // items and seed info so that if the user starts a new file
+                // they get all new items and no seed conflict.
+                let mut save = self.data.write().unwrap_or_else(PoisonError::into_inner);
+                save.items_granted = 0;
+                save.seed = None;
+                save.character = None;
+                *self
+                    .unreadable
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = None;
+                *self
+                    .last_backup
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = None;
+                *self.problem.lock().unwrap_or_else(PoisonError::into_inner) = None;
+                return;
+            }
+            LoadEvent::Other => return,
+        };
+
+        match save_format::decode::<SaveData<T>>(bytes) {
+            Ok(data) => {
+                *self
+                    .unreadable
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = None;
+                // A save without a seed hasn't connected yet, so it's expected
+                // to be behind any backups.
+                if data.seed.is_some()
+                    && let Some(backup) =
+                        self.latest_backup_for(data.seed.as_deref(), data.character)
+                    && (data.items_granted < backup.items_granted
+                        || data.locations.len() < backup.locations)
+                {
+                    self.set_problem(
+                        tr!(
+                            "save-data-went-backwards",
+                            items_granted = data.items_granted,
+                            locations = data.locations.len(),
+                            backup_items_granted = backup.items_granted,
+                            backup_locations = backup.locations,
+                        ),
+                        data.character,
+                    );
+                } else {
+                    self.back_up(data.seed.as_deref(), data.character, bytes);
+                }
+                *self.data.write().unwrap_or_else(PoisonError::into_inner) = data;
+            }
+            // Item processing stays paused until the player loads another
+            // save, even if they dismiss the error. See [DynSaveStore::is_paused].
+            Err(err @ DecodeError::TooNew { .. }) => {
+                self.keep_unreadable(bytes);
+                crate::report_hook_error(err.into());
+            }
+            // Don't use any part of a save that fails its checksum, since it
+            // may well belong to another mod entirely.
+            Err(err @ DecodeError::ChecksumMismatch { .. }) => {
+                self.keep_unreadable(bytes);
+                let backups = self
+                    .slot_dir(None)
+                    .or_else(|| save_backup::backup_root().ok())
+                    .map(|dir| dir.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                self.set_problem(
+                    tr!("save-data-corrupt", error = err, backups = backups),
+                    None,
+                );
+            }
+            Err(err) => {
+                warn!("Failed to load save data: {}", err);
+                self.keep_unreadable(bytes);
+                self.set_problem(tr!("save-data-invalid", error = err), None);
+            }
+        }
+    }
+
+    /// Replaces the save data with the default and keeps `bytes`, which
+    /// couldn't be decoded, to write back when the game saves.
+    fn keep_unreadable(&self, bytes: &[u8]) {
+        *self.data.write().unwrap_or_else(PoisonError::into_inner) = Default::default();
+        *self
+            .unreadable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(bytes.to_vec());
+    }
+
+    /// Returns the directory for backups of every character playing the current
+    /// seed and slot, or None if they aren't known or `seed` is for a different
+    /// seed.
+    fn slot_dir(&self, seed: Option<&str>) -> Option<PathBuf> {
+        let session = self.session.lock().unwrap_or_else(PoisonError::into_inner);
+        let (session_seed, slot) = session.as_ref()?;
+        if seed.is_some_and(|seed| seed != session_seed) {
+            return None;
+        }
+
+        match save_backup::backup_dir(session_seed, slot) {
+            Ok(dir) => Some(dir),
+            Err(err) => {
+                warn!("Failed to locate save backups: {err:?}");
+                None
+            }
+        }
+    }
+
+    /// Returns the directory for backups of `character` in the current seed and
+    /// slot, or None if any of them aren't known or `seed` is for a different
+    /// seed.
+    fn backup_dir(&self, seed: Option<&str>, character: Option<u64>) -> Option<PathBuf> {
+        Some(save_backup::character_dir(
+            &self.slot_dir(seed)?,
+            character?,
+        ))
+    }
+
+    /// Backs up `bytes`, the encoded save data for `character` in `seed`,
+    /// unless it's identical to the last backup.
+    fn back_up(&self, seed: Option<&str>, character: Option<u64>, bytes: &[u8]) {
+        // Don't back up data that isn't associated with a seed yet, since it
+        // has nothing worth restoring.
+        if seed.is_none() {
+            return;
+        }
+        let Some(dir) = self.backup_dir(seed, character) else {
+            return;
+        };
+
+        let mut last_backup = self
+            .last_backup
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if last_backup.as_deref() == Some(bytes) {
+            return;
+        }
+        *last_backup = Some(bytes.to_vec());
+        save_backup::write_backup(dir, bytes.to_vec());
+    }
+
+    /// Returns the most recent readable backup for `character` in `seed` and
+    /// the current slot, if there is one. If `character` is None, this
+    /// considers the backups of every character in the slot.
+    fn latest_backup_for(&self, seed: Option<&str>, character: Option<u64>) -> Option<BackupInfo> {
+        let slot_dir = self.slot_dir(seed)?;
+        let backups = match character {
+            Some(character) => {
+                save_backup::backups(&save_backup::character_dir(&slot_dir, character))
+            }
+            None => save_backup::all_backups(&slot_dir),
+        }
+        .inspect_err(|err| warn!("Failed to list save backups: {err:?}"))
+        .ok()?;
+        backups
+            .into_iter()
+            .find_map(|path| match self.read_backup(&path) {
+                Ok(data) => Some(BackupInfo {
+                    items_granted: data.items_granted,
+                    locations: data.locations.len(),
+                    path,
+                }),
+                Err(err) => {
+                    warn!(
+                        "Skipping unreadable backup {}: {err:?}",
+                        path.to_string_lossy()
+                    );
+                    None
+                }
+            })
+    }
+
+    /// Reads and decodes the backup at `path`.
+    fn read_backup(&self, path: &Path) -> Result<SaveData<T>> {
+        let bytes = std::fs::read(path)?;
+        save_format::decode(&bytes).map_err(Error::from)
+    }
+
+    /// Records a problem with the save data that was just loaded, so the
+    /// player is offered the most recent backup of `character`. If the
+    /// character isn't known, they're offered the most recent backup of any
+    /// character in the current seed and slot instead.
+    fn set_problem(&self, message: String, character: Option<u64>) {
+        warn!("{message}");
+        let backup = self.latest_backup_for(None, character);
+        *self.problem.lock().unwrap_or_else(PoisonError::into_inner) = Some(SaveProblem {
+            message,
+            backup,
+            any_character: character.is_none(),
+            discards_save: self
+                .unreadable
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some(),
+        });
+    }
+}
+
+/// Returns a new random [SaveData::character].
+fn new_character_id() -> u64 {
+    RandomState::new().hash_one(SystemTime::now())
+}
+
+impl<T: VersionedSave + Default + Send + Sync + 'static, H: SaveHook> Default for SaveStore<T, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [SaveStore] with its type parameters erased, so that shared code can work
+/// with any game's save data as JSON.
+pub trait DynSaveStore: Sync {
+    /// Returns the current save data as JSON, or None if the player isn't
+    /// currently loaded into a game.
+    fn to_json(&self) -> Option<json::Value>;
+
+    /// Checks that `value` is valid save data for this game and returns it in
+    /// the same canonical form that [Self::to_json] produces.
+    fn validate_json(&self, value: json::Value) -> Result<json::Value>;
+
+    /// Replaces the current save data with `value`. Returns an error if it's
+    /// invalid or if the player isn't currently loaded into a game.
+    fn import_json(&self, value: json::Value) -> Result<()>;
+
+    /// Sets the seed and slot the player is currently playing, which determine
+    /// where backups are stored.
+    fn set_session(&self, seed: &str, slot: &str);
+
+    /// Returns the problem with the most recently loaded save data that the
+    /// player hasn't dealt with yet, if any.
+    fn problem(&self) -> Option<SaveProblem>;
+
+    /// Dismisses the current [SaveProblem] without changing the save data. If
+    /// the loaded data couldn't be decoded, this accepts the default data in
+    /// its place, which is written to the save file from then on.
+    fn dismiss_problem(&self);
+
+    /// Returns whether the mod should stop granting items and syncing
+    /// locations, either because the loaded save data can't be used or because
+    /// the player hasn't dealt with a [SaveProblem] yet. An unreadable save
+    /// keeps this true even if the player dismisses the error it caused.
+    fn is_paused(&self) -> bool;
+
+    /// Replaces the current save data with the backup at `path` and dismisses
+    /// the current [SaveProblem].
+    fn restore_backup(&self, path: &Path) -> Result<()>;
+}
+
+impl<T, H> DynSaveStore for SaveStore<T, H>
+where
+    T: VersionedSave + Default + Serialize + DeserializeOwned + Send + Sync + 'static,
+    H: SaveHook,
+{
+    fn to_json(&self) -> Option<json::Value> {
+        json::to_value(&*self.instance()?).ok()
+    }
+
+    fn validate_json(&self, value: json::Value) -> Result<json::Value> {
+        let data: SaveData<T> = json::from_value(value).context("Invalid save data")?;
+        Ok(json::to_value(&data)?)
+    }
+
+    fn import_json(&self, value: json::Value) -> Result<()> {
+        self.replace(json::from_value(value).context("Invalid save data")?)
+    }
+
+    fn set_session(&self, seed: &str, slot: &str) {
+        let mut session = self.session.lock().unwrap_or_else(PoisonError::into_inner);
+        if session
+            .as_ref()
+            .is_none_or(|(old_seed, old_slot)| old_seed != seed || old_slot != slot)
+        {
+            *session = Some((seed.to_string(), slot.to_string()));
+        }
+    }
+
+    fn problem(&self) -> Option<SaveProblem> {
+        self.problem
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn dismiss_problem(&self) {
+        let mut problem = self.problem.lock().unwrap_or_else(PoisonError::into_inner);
+        // Saves that are too new don't raise a problem, so this only gives up
+        // data that's damaged.
+        if problem.take().is_some() {
+            *self
+                .unreadable
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = None;
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        // If the save is unreadable, the data we'd be granting items against is
+        // just a placeholder for the newer save, which will be written back
+        // unchanged. If there's a problem, the player may yet restore a backup
+        // instead.
+        self.unreadable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+            || self
+                .problem
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .is_some()
+    }
+
+    fn restore_backup(&self, path: &Path) -> Result<()> {
+        let data = self
+            .read_backup(path)
+            .with_context(|| format!("Failed to read backup {}", path.to_string_lossy()))?;
+        self.replace(data)?;
+        info!("Restored save data from {}", path.to_string_lossy());
+        self.dismiss_problem();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Game-specific data for the fixtures below.
+    #[derive(Debug, Default, PartialEq, bincode::Encode, bincode::Decode)]
+    struct TestGame {
+        count: u32,
+    }
+
+    impl VersionedSave for TestGame {
+        const VERSION: u32 = 1;
+
+        fn migrate(version: u32, _bytes: &[u8]) -> Result<Self> {
+            bail!("Unknown test game version {version}")
+        }
+    }
+
+    /// The fields every layout starts with, as laid out by [SaveDataV1]:
+    /// `items_granted` 5, `locations` {42}, `seed` "seed", and `deaths` 2.
+    const COMMON_FIELDS: &[u8] = &[
+        0x05, // items_granted
+        0x01, 0x54, // locations: length 1, zigzag-encoded 42
+        0x01, 0x04, b's', b'e', b'e', b'd', // seed: Some, length 4
+        0x02, // deaths
+    ];
+
+    /// A save written before any header existed.
+    fn headerless_v0() -> Vec<u8> {
+        COMMON_FIELDS.to_vec()
+    }
+
+    /// A save written with [SaveDataV1]'s layout after the header marker was
+    /// added.
+    fn marked_v1() -> Vec<u8> {
+        [[0xFF, 0x01].as_slice(), COMMON_FIELDS].concat()
+    }
+
+    /// A save written with [SaveDataV2]'s layout, whose game data is a nested
+    /// blob from the same era.
+    fn marked_v2() -> Vec<u8> {
+        [
+            [0xFF, 0x02].as_slice(),
+            COMMON_FIELDS,
+            // game: length 3, then version 1 with count 7
+            &[0x03, 0xFF, 0x01, 0x07],
+        ]
+        .concat()
+    }
+
+    /// A save in the current layout, with a checksum and a character.
+    const CURRENT_V3: &[u8] = &[
+        0xFE, b'A', b'P', b'S', // magic
+        0x70, 0x13, 0x3B, 0x50, // checksum
+        0x03, // version
+        0x05, 0x01, 0x54, 0x01, 0x04, b's', b'e', b'e', b'd', 0x02, // common fields
+        // game: length 10, then a checksummed version 1 blob with count 7
+        0x0A, 0xFE, b'A', b'P', b'S', 0x1D, 0xB6, 0xA6, 0xC6, 0x01, 0x07,
+        // character: Some(0x1234)
+        0x01, 0xFB, 0x34, 0x12,
+    ];
+
+    /// Asserts that `data` has the common fields from [COMMON_FIELDS].
+    fn assert_common_fields(data: &SaveData<TestGame>) {
+        assert_eq!(data.items_granted, 5);
+        assert_eq!(data.locations, HashSet::from([42]));
+        assert_eq!(data.seed.as_deref(), Some("seed"));
+        assert_eq!(data.deaths, 2);
+    }
+
+    #[test]
+    fn decodes_headerless_v0() {
+        let data = save_format::decode::<SaveData<TestGame>>(&headerless_v0()).unwrap();
+        assert_common_fields(&data);
+        assert_eq!(data.game, TestGame::default());
+        assert_eq!(data.character, None);
+    }
+
+    #[test]
+    fn decodes_marked_v1() {
+        let data = save_format::decode::<SaveData<TestGame>>(&marked_v1()).unwrap();
+        assert_common_fields(&data);
+        assert_eq!(data.game, TestGame::default());
+        assert_eq!(data.character, None);
+    }
+
+    #[test]
+    fn decodes_marked_v2() {
+        let data = save_format::decode::<SaveData<TestGame>>(&marked_v2()).unwrap();
+        assert_common_fields(&data);
+        assert_eq!(data.game, TestGame { count: 7 });
+        assert_eq!(data.character, None);
+    }
+
+    #[test]
+    fn decodes_current_v3() {
+        let data = save_format::decode::<SaveData<TestGame>>(CURRENT_V3).unwrap();
+        assert_common_fields(&data);
+        assert_eq!(data.game, TestGame { count: 7 });
+        assert_eq!(data.character, Some(0x1234));
+    }
+
+    #[test]
+    fn encodes_current_v3() {
+        let data = SaveData {
+            items_granted: 5,
+            locations: HashSet::from([42]),
+            seed: Some("seed".into()),
+            deaths: 2,
+            game: TestGame { count: 7 },
+            character: Some(0x1234),
+        };
+        assert_eq!(save_format::encode(&data).unwrap(), CURRENT_V3);
+    }
+
+    #[test]
+    fn upgrades_old_saves_to_current_layout() {
+        for bytes in [headerless_v0(), marked_v1(), marked_v2()] {
+            let mut data = save_format::decode::<SaveData<TestGame>>(&bytes).unwrap();
+            data.game.count = 7;
+            data.character = Some(0x1234);
+            assert_eq!(save_format::encode(&data).unwrap(), CURRENT_V3);
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes_in_old_saves() {
+        let bytes = [marked_v1().as_slice(), &[0x00]].concat();
+        assert!(matches!(
+            save_format::decode::<SaveData<TestGame>>(&bytes),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+}
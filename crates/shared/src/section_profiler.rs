@@ -1,12 +1,107 @@
 #[cfg(feature = "profile")]
+use std::collections::VecDeque;
+#[cfg(feature = "profile")]
 use std::fmt::Write;
 #[cfg(feature = "profile")]
+use std::path::PathBuf;
+#[cfg(feature = "profile")]
 use std::time::{Duration, Instant};
+#[cfg(feature = "profile")]
+use std::{fs, process};
 
+#[cfg(feature = "profile")]
+use anyhow::{Result, bail};
+#[cfg(feature = "profile")]
+use chrono::prelude::*;
 #[cfg(feature = "profile")]
 use indexmap::IndexMap;
 #[cfg(feature = "profile")]
 use log::*;
+#[cfg(feature = "profile")]
+use serde::Serialize;
+
+#[cfg(feature = "profile")]
+use crate::utils;
+
+/// The duration between debug prints of the frame timing data. Warnings about
+/// frames that go over the budget are batched up over the same period.
+#[cfg(feature = "profile")]
+pub(crate) const TIME_PER_FRAME_PRINT: Duration = Duration::from_secs(10);
+
+/// The number of frames of history to keep for the live profiler view.
+#[cfg(feature = "profile")]
+const HISTORY_FRAMES: usize = 240;
+
+/// The maximum number of samples to keep while capturing a trace. At roughly a
+/// dozen sections per frame, this covers several minutes at 60 FPS.
+#[cfg(feature = "profile")]
+const TRACE_CAPACITY: usize = 1 << 18;
+
+/// The number of [Histogram] buckets for each doubling of a sample's duration.
+/// Each bucket is about 9% wider than the last, which bounds the error of the
+/// percentiles we report.
+#[cfg(feature = "profile")]
+const BUCKETS_PER_OCTAVE: u32 = 8;
+
+/// The total number of [Histogram] buckets. The first bucket holds samples
+/// under 1µs and the last holds samples of 2^24µs (about 16s) or more.
+#[cfg(feature = "profile")]
+const HISTOGRAM_BUCKETS: usize = 24 * BUCKETS_PER_OCTAVE as usize + 2;
+
+/// A histogram of sample durations with logarithmically-sized buckets. This
+/// uses a fixed amount of memory no matter how many samples it holds.
+#[cfg(feature = "profile")]
+struct Histogram {
+    /// The number of samples in each bucket.
+    counts: [u32; HISTOGRAM_BUCKETS],
+}
+
+#[cfg(feature = "profile")]
+impl Default for Histogram {
+    fn default() -> Self {
+        Histogram {
+            counts: [0; HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+impl Histogram {
+    /// Adds `sample` to the histogram.
+    fn add(&mut self, sample: Duration) {
+        let micros = sample.as_secs_f64() * 1_000_000.0;
+        let bucket = if micros < 1.0 {
+            0
+        } else {
+            (micros.log2() * f64::from(BUCKETS_PER_OCTAVE)) as usize + 1
+        };
+        self.counts[bucket.min(HISTOGRAM_BUCKETS - 1)] += 1;
+    }
+
+    /// Returns the upper bound of the bucket that contains the `percentile`th
+    /// sample, where `percentile` is between 0 and 1, or None if the histogram
+    /// is empty.
+    fn percentile(&self, percentile: f64) -> Option<Duration> {
+        let total = self.counts.iter().map(|&c| u64::from(c)).sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = ((percentile * total as f64).ceil() as u64).clamp(1, total);
+        let mut seen = 0;
+        let bucket = self
+            .counts
+            .iter()
+            .position(|&count| {
+                seen += u64::from(count);
+                seen >= rank
+            })
+            .unwrap();
+        Some(Duration::from_secs_f64(
+            2f64.powf(bucket as f64 / f64::from(BUCKETS_PER_OCTAVE)) / 1_000_000.0,
+        ))
+    }
+}
 
 /// Information accumulated from multiple samples across a period of time.
 #[derive(Default)]
@@ -20,20 +115,181 @@ struct SampleSet {
 
     /// The number of samples active when recording this one.
     depth: usize,
+
+    /// The shortest sample.
+    min: Duration,
+
+    /// The longest sample.
+    max: Duration,
+
+    /// The distribution of all samples.
+    histogram: Histogram,
 }
 
 #[cfg(feature = "profile")]
 impl SampleSet {
     /// Adds `sample` to the set.
     fn add(&mut self, sample: Duration) {
+        self.min = if self.length == 0 {
+            sample
+        } else {
+            self.min.min(sample)
+        };
+        self.max = self.max.max(sample);
         self.sum += sample;
         self.length += 1;
+        self.histogram.add(sample);
     }
 
     /// The average time per sample.
     fn average(&self) -> Duration {
         self.sum / self.length
     }
+
+    /// Returns a summary of the distribution of samples, or None if there
+    /// aren't any.
+    fn stats(&self) -> Option<SectionStats> {
+        // Histogram buckets are only approximate, so make sure the percentiles
+        // stay within the range of samples we actually saw.
+        let percentile = |p| Some(self.histogram.percentile(p)?.clamp(self.min, self.max));
+        Some(SectionStats {
+            min: self.min,
+            max: self.max,
+            p50: percentile(0.5)?,
+            p95: percentile(0.95)?,
+            p99: percentile(0.99)?,
+        })
+    }
+}
+
+/// A summary of the distribution of samples for a single section since the last
+/// [SectionProfiler::report].
+#[derive(Debug, Clone, Copy)]
+#[cfg(feature = "profile")]
+pub struct SectionStats {
+    /// The shortest sample.
+    pub min: Duration,
+
+    /// The longest sample.
+    pub max: Duration,
+
+    /// The median sample, approximately.
+    pub p50: Duration,
+
+    /// The 95th percentile sample, approximately.
+    pub p95: Duration,
+
+    /// The 99th percentile sample, approximately.
+    pub p99: Duration,
+}
+
+/// Per-frame timing history for a single section, used to display live data
+/// in the overlay.
+#[derive(Default)]
+#[cfg(feature = "profile")]
+pub struct SectionHistory {
+    /// The number of samples active when recording this section.
+    pub depth: usize,
+
+    /// The time spent in this section during each of the last
+    /// [HISTORY_FRAMES] frames, in milliseconds, from oldest to newest.
+    pub frames: VecDeque<f32>,
+
+    /// The time spent in this section so far during the current frame.
+    current: Duration,
+}
+
+#[cfg(feature = "profile")]
+impl SectionHistory {
+    /// The average time spent in this section per frame across [frames], in
+    /// milliseconds.
+    pub fn average(&self) -> f32 {
+        average(&self.frames)
+    }
+}
+
+/// A single sample recorded while capturing a trace.
+#[cfg(feature = "profile")]
+struct TraceSample {
+    /// The name of the section that was sampled.
+    section: &'static str,
+
+    /// The time the sample began being recorded.
+    start: Instant,
+
+    /// How long the sample took.
+    duration: Duration,
+
+    /// The number of samples active when recording this one.
+    depth: usize,
+}
+
+/// A single event in the [Chrome Trace Event format], which can be loaded by
+/// Perfetto and `chrome://tracing`.
+///
+/// [Chrome Trace Event format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+#[derive(Serialize)]
+#[cfg(feature = "profile")]
+struct TraceEvent {
+    /// The name of the section.
+    name: &'static str,
+
+    /// The event type. This is always "X", a complete event with a duration.
+    ph: &'static str,
+
+    /// The start time of the event in microseconds.
+    ts: f64,
+
+    /// The duration of the event in microseconds.
+    dur: f64,
+
+    /// The process ID.
+    pid: u32,
+
+    /// The thread ID. All sections are recorded on the same logical thread,
+    /// since the profiler is only ever used behind the core's mutex.
+    tid: u32,
+
+    /// Extra data to display alongside the event.
+    args: TraceArgs,
+}
+
+/// The extra data attached to each [TraceEvent].
+#[derive(Serialize)]
+#[cfg(feature = "profile")]
+struct TraceArgs {
+    /// The number of samples active when recording this one.
+    depth: usize,
+}
+
+/// The top-level object of a Chrome Trace Event file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg(feature = "profile")]
+struct TraceFile {
+    /// All the events in the trace.
+    trace_events: Vec<TraceEvent>,
+
+    /// The unit in which to display times.
+    display_time_unit: &'static str,
+}
+
+/// The frames that went over the [SectionProfiler]'s budget since the last
+/// warning about them.
+#[cfg(feature = "profile")]
+struct BudgetOverruns {
+    /// The number of frames that went over the budget.
+    count: u32,
+
+    /// The time the mod's top-level sections took in the worst frame.
+    worst: Duration,
+
+    /// The slowest section in the worst frame and the time it took.
+    slowest: Option<(&'static str, Duration)>,
+
+    /// The time at which the last warning was logged, or the profiler was
+    /// created if there hasn't been one.
+    last_warning: Instant,
 }
 
 /// An actively running sample for a given [SectionProfiler] section.
@@ -62,6 +318,37 @@ pub struct SectionProfiler {
     /// The number of samples currently being recorded.
     #[cfg(feature = "profile")]
     depth: usize,
+
+    /// A map from section names to their per-frame history.
+    #[cfg(feature = "profile")]
+    history: IndexMap<&'static str, SectionHistory>,
+
+    /// The total length of each of the last [HISTORY_FRAMES] frames, in
+    /// milliseconds, from oldest to newest.
+    #[cfg(feature = "profile")]
+    frames: VecDeque<f32>,
+
+    /// The time at which the current frame began.
+    #[cfg(feature = "profile")]
+    frame_start: Instant,
+
+    /// Whether recording history is paused. This doesn't affect [report].
+    #[cfg(feature = "profile")]
+    paused: bool,
+
+    /// A ring buffer of the most recent individual samples, or None if we're
+    /// not capturing a trace.
+    #[cfg(feature = "profile")]
+    trace: Option<VecDeque<TraceSample>>,
+
+    /// The longest the mod's top-level sections may take in a single frame
+    /// before we log a warning, or None if there's no limit.
+    #[cfg(feature = "profile")]
+    budget: Option<Duration>,
+
+    /// The frames that have gone over [budget] since we last warned about it.
+    #[cfg(feature = "profile")]
+    overruns: BudgetOverruns,
 }
 
 impl SectionProfiler {
@@ -72,6 +359,18 @@ impl SectionProfiler {
             last_report: Instant::now(),
             averages: Default::default(),
             depth: 0,
+            history: Default::default(),
+            frames: Default::default(),
+            frame_start: Instant::now(),
+            paused: false,
+            trace: None,
+            budget: None,
+            overruns: BudgetOverruns {
+                count: 0,
+                worst: Duration::ZERO,
+                slowest: None,
+                last_warning: Instant::now(),
+            },
         }
     }
 
@@ -87,6 +386,7 @@ impl SectionProfiler {
         // Add an entry if one doesn't exist so that the profiler sections
         // appear in source order.
         self.averages.entry(section).or_default().depth = self.depth;
+        self.history.entry(section).or_default().depth = self.depth;
         self.depth += 1;
         Sample {
             section,
@@ -104,10 +404,24 @@ impl SectionProfiler {
     #[cfg(feature = "profile")]
     pub fn record_sample(&mut self, sample: Sample) {
         self.depth -= 1;
+        let elapsed = Instant::now().duration_since(sample.start);
         self.averages
             .entry(sample.section)
             .or_default()
-            .add(Instant::now().duration_since(sample.start));
+            .add(elapsed);
+        self.history.entry(sample.section).or_default().current += elapsed;
+
+        if let Some(trace) = &mut self.trace {
+            if trace.len() >= TRACE_CAPACITY {
+                trace.pop_front();
+            }
+            trace.push_back(TraceSample {
+                section: sample.section,
+                start: sample.start,
+                duration: elapsed,
+                depth: self.depth,
+            });
+        }
     }
 
     #[cfg(not(feature = "profile"))]
@@ -128,7 +442,7 @@ impl SectionProfiler {
         let total_time_per_frame = elapsed / frames;
         for (section, samples) in self.averages.iter() {
             let time_per_frame = samples.average();
-            let _ = writeln!(
+            let _ = write!(
                 message,
                 "{}{section} took {:?}/frame avg ({:.2}% of frame)",
                 "  ".repeat(samples.depth + 1),
@@ -136,6 +450,14 @@ impl SectionProfiler {
                 100.0 * (time_per_frame.as_micros() as f64)
                     / (total_time_per_frame.as_micros() as f64),
             );
+            if let Some(stats) = samples.stats() {
+                let _ = write!(
+                    message,
+                    "; min {:?}, p50 {:?}, p95 {:?}, p99 {:?}, max {:?}",
+                    stats.min, stats.p50, stats.p95, stats.p99, stats.max,
+                );
+            }
+            message.push('\n');
         }
 
         info!("{}", message.trim_end());
@@ -147,6 +469,249 @@ impl SectionProfiler {
     #[cfg(not(feature = "profile"))]
     #[inline]
     pub fn report(&mut self) {}
+
+    /// Marks the end of a frame, adding the time spent in each section during
+    /// that frame to its history unless the profiler is paused. This should be
+    /// called exactly once per frame.
+    #[cfg(feature = "profile")]
+    pub fn end_frame(&mut self) {
+        let now = Instant::now();
+        let frame = now.duration_since(self.frame_start);
+        self.frame_start = now;
+
+        if let Some(budget) = self.budget {
+            self.check_budget(budget);
+        }
+
+        for section in self.history.values_mut() {
+            let current = std::mem::take(&mut section.current);
+            if !self.paused {
+                push_history(&mut section.frames, current);
+            }
+        }
+        if !self.paused {
+            push_history(&mut self.frames, frame);
+        }
+    }
+
+    #[cfg(not(feature = "profile"))]
+    #[inline]
+    pub fn end_frame(&mut self) {}
+
+    /// Returns a human-readable summary of the profiler's current state, for
+    /// inclusion in diagnostic bundles.
+    #[cfg(feature = "profile")]
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Average frame: {:.3} ms over {} frames\nPaused: {}\nCapturing trace: {}\n\
+             Budget: {:?}\n",
+            self.average_frame(),
+            self.frames.len(),
+            self.paused,
+            self.is_capturing(),
+            self.budget,
+        );
+        for (section, history) in &self.history {
+            let _ = write!(
+                summary,
+                "{}{section}: {:.3} ms/frame avg",
+                "  ".repeat(history.depth + 1),
+                history.average(),
+            );
+            if let Some(stats) = self.stats(section) {
+                let _ = write!(
+                    summary,
+                    "; min {:?}, p50 {:?}, p95 {:?}, p99 {:?}, max {:?}",
+                    stats.min, stats.p50, stats.p95, stats.p99, stats.max,
+                );
+            }
+            summary.push('\n');
+        }
+        summary
+    }
+
+    #[cfg(not(feature = "profile"))]
+    pub fn summary(&self) -> String {
+        "Profiling is disabled in this build.\n".into()
+    }
+
+    /// Records whether the mod's top-level sections took longer than `budget`
+    /// during the current frame, and logs a warning summarizing the frames
+    /// that did at most once every [TIME_PER_FRAME_PRINT]. The warning names
+    /// the slowest section nested within the worst frame.
+    #[cfg(feature = "profile")]
+    fn check_budget(&mut self, budget: Duration) {
+        let total = self
+            .history
+            .values()
+            .filter(|section| section.depth == 0)
+            .map(|section| section.current)
+            .sum::<Duration>();
+        if total > budget {
+            let overruns = &mut self.overruns;
+            overruns.count += 1;
+            if total > overruns.worst {
+                overruns.worst = total;
+
+                // Prefer naming a nested section, since the top-level sections
+                // are too broad to be useful on their own.
+                overruns.slowest = self
+                    .history
+                    .iter()
+                    .filter(|(_, section)| section.depth > 0)
+                    .max_by_key(|(_, section)| section.current)
+                    .or_else(|| {
+                        self.history
+                            .iter()
+                            .max_by_key(|(_, section)| section.current)
+                    })
+                    .map(|(name, section)| (*name, section.current));
+            }
+        }
+
+        let overruns = &mut self.overruns;
+        if overruns.count == 0 || overruns.last_warning.elapsed() < TIME_PER_FRAME_PRINT {
+            return;
+        }
+
+        let mut message = format!(
+            "{} frame(s) went over the budget of {budget:?} in the last {:?}. \
+             The worst took {:?}",
+            overruns.count,
+            overruns.last_warning.elapsed(),
+            overruns.worst,
+        );
+        if let Some((name, duration)) = overruns.slowest {
+            let _ = write!(message, ", with the slowest section {name} ({duration:?})");
+        }
+        warn!("{message}.");
+
+        overruns.count = 0;
+        overruns.worst = Duration::ZERO;
+        overruns.slowest = None;
+        overruns.last_warning = Instant::now();
+    }
+
+    /// Returns a summary of the distribution of samples for `section` since the
+    /// last [report], or None if it hasn't been sampled.
+    #[cfg(feature = "profile")]
+    pub fn stats(&self, section: &str) -> Option<SectionStats> {
+        self.averages.get(section)?.stats()
+    }
+
+    /// Returns the longest the mod's top-level sections may take in a single
+    /// frame before a warning is logged, or None if there's no limit.
+    #[cfg(feature = "profile")]
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// Sets the longest the mod's top-level sections may take in a single frame
+    /// before a warning is logged.
+    #[cfg(feature = "profile")]
+    pub fn set_budget(&mut self, budget: Option<Duration>) {
+        self.budget = budget;
+    }
+
+    /// Returns the history of each section that has been recorded, in the
+    /// order in which they were first seen.
+    #[cfg(feature = "profile")]
+    pub fn history(&self) -> impl Iterator<Item = (&'static str, &SectionHistory)> {
+        self.history.iter().map(|(name, history)| (*name, history))
+    }
+
+    /// The average length of a frame across [frames], in milliseconds.
+    #[cfg(feature = "profile")]
+    pub fn average_frame(&self) -> f32 {
+        average(&self.frames)
+    }
+
+    /// Returns whether recording history is paused.
+    #[cfg(feature = "profile")]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Sets whether recording history is paused.
+    #[cfg(feature = "profile")]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Discards all recorded history.
+    #[cfg(feature = "profile")]
+    pub fn reset_history(&mut self) {
+        self.history.clear();
+        self.frames.clear();
+    }
+
+    /// Returns whether individual samples are being captured for a trace.
+    #[cfg(feature = "profile")]
+    pub fn is_capturing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    /// Starts or stops capturing individual samples for a trace. Stopping
+    /// discards any samples that have been captured.
+    #[cfg(feature = "profile")]
+    pub fn set_capturing(&mut self, capturing: bool) {
+        if capturing != self.is_capturing() {
+            self.trace = capturing.then(VecDeque::new);
+        }
+    }
+
+    /// Writes all captured samples that ended within the last `duration` to a
+    /// Chrome Trace Event JSON file in the mod's log directory, and returns the
+    /// path to that file.
+    #[cfg(feature = "profile")]
+    pub fn write_trace(&self, duration: Duration) -> Result<PathBuf> {
+        let Some(trace) = &self.trace else {
+            bail!("No trace is being captured");
+        };
+
+        // If `duration` reaches back further than [Instant] can represent,
+        // include every sample.
+        let now = Instant::now();
+        let cutoff = now
+            .checked_sub(duration)
+            .or_else(|| trace.front().map(|sample| sample.start))
+            .unwrap_or(now);
+        let samples = trace
+            .iter()
+            .filter(|sample| sample.start + sample.duration >= cutoff)
+            .collect::<Vec<_>>();
+        let Some(epoch) = samples.iter().map(|sample| sample.start).min() else {
+            bail!("No samples were captured in the last {duration:?}");
+        };
+
+        let file = TraceFile {
+            trace_events: samples
+                .into_iter()
+                .map(|sample| TraceEvent {
+                    name: sample.section,
+                    ph: "X",
+                    ts: sample.start.duration_since(epoch).as_secs_f64() * 1_000_000.0,
+                    dur: sample.duration.as_secs_f64() * 1_000_000.0,
+                    pid: process::id(),
+                    tid: 0,
+                    args: TraceArgs {
+                        depth: sample.depth,
+                    },
+                })
+                .collect(),
+            display_time_unit: "ms",
+        };
+
+        let dir = utils::mod_directory()?.join("log");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(
+            Local::now()
+                .format("trace-%Y-%m-%d-%H-%M-%S.json")
+                .to_string(),
+        );
+        fs::write(&path, json::to_string(&file)?)?;
+        Ok(path)
+    }
 }
 
 impl Default for SectionProfiler {
@@ -155,6 +720,26 @@ impl Default for SectionProfiler {
     }
 }
 
+/// Adds `sample` to the end of `history` in milliseconds, dropping the oldest
+/// entry if it has more than [HISTORY_FRAMES] entries.
+#[cfg(feature = "profile")]
+fn push_history(history: &mut VecDeque<f32>, sample: Duration) {
+    if history.len() >= HISTORY_FRAMES {
+        history.pop_front();
+    }
+    history.push_back(sample.as_secs_f32() * 1000.0);
+}
+
+/// Returns the average of `values`, or 0 if it's empty.
+#[cfg(feature = "profile")]
+fn average(values: &VecDeque<f32>) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
+
 /// A helper macro for profiling a section of code using a [SectionProfiler].
 ///
 /// **Warning:** This intentionally executes `$profiler` multiple times so that
@@ -1,13 +1,18 @@
+use std::ffi::c_void;
 use std::os::windows::ffi::OsStringExt;
 use std::path::{Path, PathBuf};
-use std::{cmp, ffi::OsString, io, mem, mem::MaybeUninit, sync::LazyLock};
+use std::{cmp, ffi::OsString, io, mem, mem::MaybeUninit, ptr, sync::LazyLock};
 
-use anyhow::{Context, Error, Result};
+use anyhow::{Context, Error, Result, bail};
 use imgui::*;
 use mint::Vector2;
 use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, HMODULE, MAX_PATH};
+use windows::Win32::Storage::FileSystem::{
+    GetFileVersionInfoSizeW, GetFileVersionInfoW, VS_FIXEDFILEINFO, VerQueryValueW,
+};
 use windows::Win32::System::ProcessStatus::{ENUM_PROCESS_MODULES_EX_FLAGS, EnumProcessModulesEx};
 use windows::Win32::System::{LibraryLoader::GetModuleFileNameW, Threading::GetCurrentProcess};
+use windows::core::{HSTRING, w};
 use windows_result::Error as WindowsError;
 
 /// Returns the path to the parent directory of the mod.
@@ -101,6 +106,41 @@ fn try_load_mod_directory(size: u32) -> Result<TryLoadModDirectoryResult> {
     }
 }
 
+/// Returns the file version of the game's executable, such as "1.15.2.0".
+pub fn game_version() -> Result<String> {
+    let path = HSTRING::from(get_module_path(HMODULE::default())?.as_path());
+
+    // Safety: `path` is a valid null-terminated string.
+    let size = unsafe { GetFileVersionInfoSizeW(&path, None) };
+    if size == 0 {
+        return Err(WindowsError::from_thread().into());
+    }
+
+    let mut data = vec![0u8; usize::try_from(size)?];
+    // Safety: `data` is a writable buffer of `size` bytes.
+    unsafe { GetFileVersionInfoW(&path, None, size, data.as_mut_ptr().cast()) }?;
+
+    let mut info: *mut c_void = ptr::null_mut();
+    let mut len = 0u32;
+    // Safety: `data` was filled by GetFileVersionInfoW, and `info` will point
+    // into it if this succeeds.
+    let found = unsafe { VerQueryValueW(data.as_ptr().cast(), w!("\\"), &mut info, &mut len) };
+    if !found.as_bool() || (len as usize) < mem::size_of::<VS_FIXEDFILEINFO>() {
+        bail!("The game executable has no version information");
+    }
+
+    // Safety: VerQueryValueW returned a pointer to a VS_FIXEDFILEINFO within
+    // `data`, which is still alive.
+    let info = unsafe { &*info.cast::<VS_FIXEDFILEINFO>() };
+    Ok(format!(
+        "{}.{}.{}.{}",
+        info.dwFileVersionMS >> 16,
+        info.dwFileVersionMS & 0xffff,
+        info.dwFileVersionLS >> 16,
+        info.dwFileVersionLS & 0xffff,
+    ))
+}
+
 /// The value returned by [try_load_mod_directory]
 enum TryLoadModDirectoryResult {
     /// The path to the mod directory.